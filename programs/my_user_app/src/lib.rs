@@ -1,98 +1,4018 @@
+// Anchor 0.31 generates IDL instructions at the crate root that still call the deprecated
+// `AccountInfo::realloc`; this crate itself uses `AccountInfo::resize`.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::solana_program::hash::hash;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeAmount, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_2022_extensions::transfer_fee::{self, HarvestWithheldTokensToMint};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked};
+use std::collections::BTreeSet;
+
+declare_id!("EJfiMorcTnMgyHvxpBe8EaBc7YG5p79xy4vLe2fPqV3B");
+
+#[program]
+pub mod task_manager {
+    use super::*;
+
+    pub fn create_task(
+        ctx: Context<CreateTask>,
+        name: String,
+        start_at: Option<i64>,
+        due_at: Option<i64>,
+    ) -> Result<()> {
+        validate_name(&name, &ctx.accounts.config)?;
+        let now = Clock::get()?.unix_timestamp;
+        validate_schedule(start_at, due_at, now)?;
+        let fee = ctx.accounts.config.task_fee;
+        if fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.config.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+        let profile = &mut ctx.accounts.user_profile;
+        let task = &mut ctx.accounts.task_account;
+        task.version = TaskAccount::VERSION;
+        task.name = name;
+        task.authority = *ctx.accounts.user.key;
+        task.creator = task.authority;
+        if let Some(project) = ctx.accounts.project.as_mut() {
+            if project.owner != task.authority {
+                return err!(ErrorCode::UnauthorizedAction);
+            }
+            if project.closed {
+                return err!(ErrorCode::ProjectClosed);
+            }
+            task.id = project.next_task_id;
+            task.project = Some(project.key());
+            task.approval_policy = project.approval_policy;
+            task.rank = append_rank(&mut project.last_rank, TaskAccount::initial_rank(task.id));
+            project.next_task_id += 1;
+            project.open_task_count += 1;
+        } else {
+            task.id = profile.next_task_id;
+            task.rank = append_rank(&mut profile.last_rank, TaskAccount::initial_rank(task.id));
+            profile.next_task_id += 1;
+        }
+        task.status = TaskStatus::Todo;
+        task.start_at = start_at;
+        task.due_at = due_at;
+        task.created_at = now;
+        task.updated_at = now;
+        if let Some(parent) = ctx.accounts.parent_task.as_mut() {
+            if parent.authority != task.authority {
+                return err!(ErrorCode::UnauthorizedAction);
+            }
+            if !parent.status.is_active() {
+                return err!(ErrorCode::ParentTaskClosed);
+            }
+            if parent.project != task.project {
+                return err!(ErrorCode::ProjectMismatch);
+            }
+            task.parent = Some(parent.key());
+            parent.child_count += 1;
+            parent.open_child_count += 1;
+            parent.touch(now);
+        }
+        profile.authority = task.authority;
+        profile.active_task_count += 1;
+        msg!("Task '{}' created with ID: {}", task.name, task.id);
+        Ok(())
+    }
+
+    /// Compatibility shim for clients that only know active/inactive: `false` closes an open
+    /// task as Done and `true` reopens a closed one as Todo, bypassing the transition table.
+    pub fn update_task_status(
+        ctx: Context<UpdateTaskStatus>,
+        new_status: bool,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.authorize()?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        if task.status == TaskStatus::Archived {
+            return err!(ErrorCode::TaskArchived);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        if task.status.is_active() == new_status {
+            task.touch(now);
+        } else {
+            let status = if new_status { TaskStatus::Todo } else { TaskStatus::Done };
+            let accounts = &mut *ctx.accounts;
+            apply_status(
+                &mut accounts.task_account,
+                &mut accounts.user_profile,
+                &mut accounts.parent_task,
+                &mut accounts.project,
+                status,
+                now,
+            )?;
+        }
+        msg!("Task ID {} status updated to: {:?}", ctx.accounts.task_account.id, ctx.accounts.task_account.status);
+        Ok(())
+    }
+
+    /// Moving to InProgress requires every dependency to be closed; pass each one as a
+    /// `(task dependency, depended-on task)` pair in `remaining_accounts`.
+    pub fn transition_task<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateTaskStatus<'info>>,
+        new_status: TaskStatus,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        if new_status == TaskStatus::Archived {
+            ctx.accounts.authorize_archive()?;
+        } else {
+            ctx.accounts.authorize()?;
+        }
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        if task.status == TaskStatus::Archived {
+            return err!(ErrorCode::TaskArchived);
+        }
+        if !task.status.can_transition_to(new_status) {
+            return err!(ErrorCode::InvalidStatusTransition);
+        }
+        if new_status == TaskStatus::InProgress {
+            check_dependencies_closed(task, ctx.remaining_accounts)?;
+        }
+        if new_status == TaskStatus::Archived {
+            consume_approval(task, &ctx.accounts.pending_action, &ctx.accounts.proposer, ProposedAction::Archive)?;
+        }
+        msg!("Task ID {} moved from {:?} to {:?}", task.id, task.status, new_status);
+        let now = Clock::get()?.unix_timestamp;
+        let accounts = &mut *ctx.accounts;
+        apply_status(
+            &mut accounts.task_account,
+            &mut accounts.user_profile,
+            &mut accounts.parent_task,
+            &mut accounts.project,
+            new_status,
+            now,
+        )
+    }
+
+    pub fn set_task_priority(
+        ctx: Context<ModifyTask>,
+        priority: TaskPriority,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.priority = priority;
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Task ID {} priority set to {:?}", task.id, priority);
+        Ok(())
+    }
+
+    /// Moves a task between `prev_task` and `next_task`; omit one of them to move it to the
+    /// start or end of the list. Neighbours must be in the same list, i.e. share the task's
+    /// authority and project.
+    pub fn reorder_task(ctx: Context<ReorderTask>, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let accounts = &mut *ctx.accounts;
+        let task = &accounts.task_account;
+        let neighbour_rank = |neighbour: &Option<Account<TaskAccount>>| -> Result<Option<u64>> {
+            match neighbour {
+                Some(n) if n.authority != task.authority || n.project != task.project || n.key() == task.key() => {
+                    err!(ErrorCode::InvalidNeighbours)
+                }
+                Some(n) => Ok(Some(n.rank)),
+                None => Ok(None),
+            }
+        };
+        let prev = neighbour_rank(&accounts.prev_task)?;
+        let next = neighbour_rank(&accounts.next_task)?;
+        let rank = match (prev, next) {
+            (prev, Some(next)) => rank_between(prev, next)?,
+            (Some(prev), None) => {
+                // Past every rank handed out in the list so far, so the next created task
+                // still lands after this one.
+                let last_rank = match task.project_account(&mut accounts.project)? {
+                    Some(project) => &mut project.last_rank,
+                    None => &mut accounts.user_profile.last_rank,
+                };
+                let rank = append_rank(last_rank, prev.saturating_add(RANK_SPACING));
+                if rank == prev {
+                    return err!(ErrorCode::RankGapExhausted);
+                }
+                rank
+            }
+            (None, None) => return err!(ErrorCode::InvalidNeighbours),
+        };
+
+        let task = &mut accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.rank = rank;
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Task ID {} moved to rank {}", task.id, task.rank);
+        Ok(())
+    }
+
+    pub fn create_project(ctx: Context<CreateProject>, name: String) -> Result<()> {
+        validate_label(&name, MAX_PROJECT_NAME_LENGTH)?;
+        let project = &mut ctx.accounts.project;
+        project.owner = ctx.accounts.owner.key();
+        msg!("Project '{}' created", name);
+        project.name = name;
+        Ok(())
+    }
+
+    /// Closed projects accept no new tasks and their tasks cannot be reopened.
+    pub fn close_project(ctx: Context<CloseProject>) -> Result<()> {
+        let project = &mut ctx.accounts.project;
+        if project.open_task_count > 0 {
+            return err!(ErrorCode::ProjectHasOpenTasks);
+        }
+        project.closed = true;
+        msg!("Project '{}' closed", project.name);
+        Ok(())
+    }
+
+    /// Delegates `session_key` to act on the signer's tasks with `permissions`, a combination
+    /// of the `SESSION_*` flags, until `expires_at`.
+    pub fn create_session_key(
+        ctx: Context<CreateSessionKey>,
+        session_key: Pubkey,
+        expires_at: i64,
+        permissions: u8,
+    ) -> Result<()> {
+        if expires_at <= Clock::get()?.unix_timestamp {
+            return err!(ErrorCode::SessionExpired);
+        }
+        if permissions == 0 || permissions & !SESSION_ALL != 0 {
+            return err!(ErrorCode::InvalidSessionPermissions);
+        }
+        let session = &mut ctx.accounts.session;
+        session.authority = ctx.accounts.authority.key();
+        session.session_key = session_key;
+        session.expires_at = expires_at;
+        session.permissions = permissions;
+        msg!("Session key {} created until {}", session_key, expires_at);
+        Ok(())
+    }
+
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        msg!("Session key {} revoked", ctx.accounts.session.session_key);
+        Ok(())
+    }
+
+    /// Creates the owner's workspace, whose members may act on the owner's tasks according to
+    /// their role. The owner becomes its first member.
+    pub fn create_workspace(ctx: Context<CreateWorkspace>, name: String) -> Result<()> {
+        validate_label(&name, MAX_WORKSPACE_NAME_LENGTH)?;
+        let workspace = &mut ctx.accounts.workspace;
+        workspace.owner = ctx.accounts.owner.key();
+        workspace.member_count = 1;
+        let member = &mut ctx.accounts.owner_member;
+        member.workspace = workspace.key();
+        member.user = workspace.owner;
+        member.role = MemberRole::Owner;
+        member.accepted = true;
+        msg!("Workspace '{}' created", name);
+        workspace.name = name;
+        Ok(())
+    }
+
+    /// Invites `user` with `role`; the invitation takes effect once they accept it.
+    pub fn invite_member(ctx: Context<InviteMember>, user: Pubkey, role: MemberRole) -> Result<()> {
+        ctx.accounts.actor_member.check_can_manage(role)?;
+        let member = &mut ctx.accounts.member;
+        member.workspace = ctx.accounts.workspace.key();
+        member.user = user;
+        member.role = role;
+        ctx.accounts.workspace.member_count += 1;
+        msg!("{} invited as {:?}", user, role);
+        Ok(())
+    }
+
+    pub fn accept_invitation(ctx: Context<AcceptInvitation>) -> Result<()> {
+        ctx.accounts.member.accepted = true;
+        msg!("{} joined workspace {}", ctx.accounts.user.key(), ctx.accounts.member.workspace);
+        Ok(())
+    }
+
+    pub fn change_member_role(ctx: Context<ChangeMemberRole>, role: MemberRole) -> Result<()> {
+        let member = &mut ctx.accounts.member;
+        if member.user == ctx.accounts.workspace.owner {
+            return err!(ErrorCode::WorkspaceOwnerImmutable);
+        }
+        ctx.accounts.actor_member.check_can_manage(member.role)?;
+        ctx.accounts.actor_member.check_can_manage(role)?;
+        msg!("{} changed from {:?} to {:?}", member.user, member.role, role);
+        member.role = role;
+        Ok(())
+    }
+
+    /// Members may always leave on their own; removing someone else needs a role that can
+    /// manage theirs.
+    pub fn remove_member(ctx: Context<RemoveMember>) -> Result<()> {
+        let member = &ctx.accounts.member;
+        if member.user == ctx.accounts.workspace.owner {
+            return err!(ErrorCode::WorkspaceOwnerImmutable);
+        }
+        if member.user != ctx.accounts.actor.key() {
+            match &ctx.accounts.actor_member {
+                Some(actor_member) => actor_member.check_can_manage(member.role)?,
+                None => return err!(ErrorCode::UnauthorizedAction),
+            }
+        }
+        ctx.accounts.workspace.member_count -= 1;
+        msg!("{} removed from workspace", member.user);
+        Ok(())
+    }
+
+    pub fn create_tag(ctx: Context<CreateTag>, name: String) -> Result<()> {
+        validate_label(&name, MAX_TAG_NAME_LENGTH)?;
+        let registry = &mut ctx.accounts.tag_registry;
+        registry.namespace = tag_namespace(&ctx.accounts.project, &ctx.accounts.authority);
+        registry.check_name_available(&name, None)?;
+        if registry.tags.len() >= MAX_TAGS {
+            return err!(ErrorCode::TagRegistryFull);
+        }
+        msg!("Tag '{}' created at index {}", name, registry.tags.len());
+        registry.tags.push(Tag { name, retired: false });
+        Ok(())
+    }
+
+    pub fn rename_tag(ctx: Context<ManageTags>, index: u8, name: String) -> Result<()> {
+        validate_label(&name, MAX_TAG_NAME_LENGTH)?;
+        let registry = &mut ctx.accounts.tag_registry;
+        registry.check_name_available(&name, Some(index))?;
+        registry.active_tag_mut(index)?.name = name;
+        Ok(())
+    }
+
+    /// Retired tags keep their bit forever, so tasks still carrying it are left intact; the
+    /// tag just can no longer be added to tasks or renamed.
+    pub fn retire_tag(ctx: Context<ManageTags>, index: u8) -> Result<()> {
+        ctx.accounts.tag_registry.active_tag_mut(index)?.retired = true;
+        msg!("Tag {} retired", index);
+        Ok(())
+    }
+
+    pub fn add_task_tag(ctx: Context<TagTask>, index: u8, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        ctx.accounts.tag_registry.active_tag(index)?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.tags |= 1 << index;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn remove_task_tag(ctx: Context<TagTask>, index: u8, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        ctx.accounts.tag_registry.tag(index)?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.tags &= !(1 << index);
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    /// Makes the task wait for `depends_on`. To rule out cycles, `remaining_accounts` must hold
+    /// every task reachable from `depends_on` through dependencies, along with the
+    /// `TaskDependency` accounts linking them; see `check_dependency_cycle`.
+    pub fn add_dependency<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddDependency<'info>>,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let task_key = ctx.accounts.task_account.key();
+        check_dependency_cycle(task_key, &ctx.accounts.depends_on, ctx.remaining_accounts)?;
+        let dependency = &mut ctx.accounts.task_dependency;
+        dependency.task = task_key;
+        dependency.depends_on = ctx.accounts.depends_on.key();
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.dependency_count += 1;
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Task ID {} now depends on task ID {}", task.id, ctx.accounts.depends_on.id);
+        Ok(())
+    }
+
+    pub fn remove_dependency(ctx: Context<RemoveDependency>, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.dependency_count -= 1;
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Task ID {} no longer depends on {}", task.id, ctx.accounts.depends_on.key());
+        Ok(())
+    }
+
+    /// Assigns `user` to the task, as its assignee or, once that is taken, as a co-assignee.
+    /// Assignees may change the task's status but not delete it.
+    pub fn assign_task(ctx: Context<AssignTask>, user: Pubkey, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.authorize()?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        if task.is_assignee(&user) {
+            return err!(ErrorCode::AlreadyAssigned);
+        }
+        if task.assignee.is_none() {
+            task.assignee = Some(user);
+        } else if task.co_assignees.len() < MAX_CO_ASSIGNEES {
+            task.co_assignees.push(user);
+        } else {
+            return err!(ErrorCode::AssigneesFull);
+        }
+        task.touch(Clock::get()?.unix_timestamp);
+        emit!(TaskAssigned {
+            task: task.key(),
+            assignee: user,
+            assigned_by: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    /// Removing the assignee promotes the first co-assignee. Assignees may unassign themselves.
+    pub fn unassign_task(ctx: Context<AssignTask>, user: Pubkey, expected_revision: Option<u64>) -> Result<()> {
+        if user != ctx.accounts.authority.key() {
+            ctx.accounts.authorize()?;
+        }
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        if task.assignee == Some(user) {
+            task.assignee = (!task.co_assignees.is_empty()).then(|| task.co_assignees.remove(0));
+        } else if let Some(index) = task.co_assignees.iter().position(|key| *key == user) {
+            task.co_assignees.remove(index);
+        } else {
+            return err!(ErrorCode::NotAssigned);
+        }
+        task.touch(Clock::get()?.unix_timestamp);
+        emit!(TaskUnassigned {
+            task: task.key(),
+            assignee: user,
+            unassigned_by: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    /// Offers the task to `new_owner`, who takes it over with `accept_transfer`. The offer
+    /// lapses after `expires_at_slot` when one is given.
+    pub fn propose_transfer(
+        ctx: Context<ProposeTransfer>,
+        new_owner: Pubkey,
+        expires_at_slot: Option<u64>,
+    ) -> Result<()> {
+        let accounts = &ctx.accounts;
+        consume_approval(
+            &accounts.task_account,
+            &accounts.pending_action,
+            &accounts.proposer,
+            ProposedAction::Transfer { new_owner },
+        )?;
+        let clock = Clock::get()?;
+        let task = &mut ctx.accounts.task_account;
+        if task.parent.is_some() || task.child_count > 0 {
+            return err!(ErrorCode::SubtaskTransfer);
+        }
+        if expires_at_slot.is_some_and(|slot| slot <= clock.slot) {
+            return err!(ErrorCode::TransferExpired);
+        }
+        task.pending_owner = Some(new_owner);
+        task.transfer_expires_at_slot = expires_at_slot;
+        task.touch(clock.unix_timestamp);
+        msg!("Task ID {} offered to {}", task.id, new_owner);
+        Ok(())
+    }
+
+    /// Hands the task to the pending owner, who from then on also receives its rent on delete.
+    pub fn accept_transfer(ctx: Context<AcceptTransfer>) -> Result<()> {
+        let clock = Clock::get()?;
+        let task = &mut ctx.accounts.task_account;
+        if task.transfer_expires_at_slot.is_some_and(|slot| clock.slot > slot) {
+            return err!(ErrorCode::TransferExpired);
+        }
+        let active = task.status.is_active();
+        ctx.accounts.previous_owner_profile.record_removal(active);
+        let profile = &mut ctx.accounts.new_owner_profile;
+        profile.authority = ctx.accounts.new_owner.key();
+        if active {
+            profile.active_task_count += 1;
+        } else {
+            profile.inactive_task_count += 1;
+        }
+        if task.project.is_none() {
+            // Joins the end of the new owner's list.
+            let floor = TaskAccount::initial_rank(profile.next_task_id);
+            task.rank = append_rank(&mut profile.last_rank, floor);
+        }
+        msg!("Task ID {} transferred from {} to {}", task.id, task.authority, profile.authority);
+        task.authority = profile.authority;
+        task.pending_owner = None;
+        task.transfer_expires_at_slot = None;
+        task.touch(clock.unix_timestamp);
+        Ok(())
+    }
+
+    /// Withdraws a pending transfer; either the owner or the pending owner may cancel it.
+    pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+        let task = &mut ctx.accounts.task_account;
+        let signer = ctx.accounts.signer.key();
+        match task.pending_owner {
+            None => return err!(ErrorCode::NoPendingTransfer),
+            Some(pending_owner) if signer != pending_owner && signer != task.authority => {
+                return err!(ErrorCode::UnauthorizedAction)
+            }
+            Some(_) => {}
+        }
+        task.pending_owner = None;
+        task.transfer_expires_at_slot = None;
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Transfer of task ID {} cancelled", task.id);
+        Ok(())
+    }
+
+    /// Puts the task under an approval policy, after which deleting, transferring or archiving
+    /// it needs `threshold` of `approvers` to sign off through `propose_action`. Policies
+    /// cannot be changed or removed. A pending transfer offer is withdrawn, as it was made
+    /// without the approvers' sign-off.
+    pub fn create_task_approval_policy(
+        ctx: Context<CreateTaskApprovalPolicy>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let task = &mut ctx.accounts.task_account;
+        if task.approval_policy.is_some() {
+            return err!(ErrorCode::ApprovalPolicyAlreadySet);
+        }
+        let policy = &mut ctx.accounts.approval_policy;
+        policy.init(task.key(), approvers, threshold)?;
+        task.approval_policy = Some(policy.key());
+        task.pending_owner = None;
+        task.transfer_expires_at_slot = None;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    /// Like `create_task_approval_policy`, for every task created in the project from now on.
+    pub fn create_project_approval_policy(
+        ctx: Context<CreateProjectApprovalPolicy>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let project = &mut ctx.accounts.project;
+        if project.approval_policy.is_some() {
+            return err!(ErrorCode::ApprovalPolicyAlreadySet);
+        }
+        let policy = &mut ctx.accounts.approval_policy;
+        policy.init(project.key(), approvers, threshold)?;
+        project.approval_policy = Some(policy.key());
+        Ok(())
+    }
+
+    /// Opens a vote on a destructive action for a task under an approval policy. Once enough
+    /// approvers have signed off, the action runs through its usual instruction, which is
+    /// passed the pending action and consumes it.
+    pub fn propose_action(ctx: Context<ProposeAction>, action: ProposedAction, expires_at: i64) -> Result<()> {
+        let task = &ctx.accounts.task_account;
+        let policy = &ctx.accounts.approval_policy;
+        let proposer = ctx.accounts.proposer.key();
+        if proposer != task.authority && !policy.approvers.contains(&proposer) {
+            return err!(ErrorCode::UnauthorizedAction);
+        }
+        if expires_at <= Clock::get()?.unix_timestamp {
+            return err!(ErrorCode::ActionExpired);
+        }
+        let pending = &mut ctx.accounts.pending_action;
+        pending.task = task.key();
+        pending.policy = policy.key();
+        pending.action = action;
+        pending.proposer = proposer;
+        pending.threshold = policy.threshold;
+        pending.expires_at = expires_at;
+        msg!("Task ID {}: {:?} proposed by {}", task.id, action, proposer);
+        Ok(())
+    }
+
+    pub fn approve_action(ctx: Context<ApproveAction>) -> Result<()> {
+        let approver = ctx.accounts.approver.key();
+        let Some(index) = ctx.accounts.approval_policy.approvers.iter().position(|key| *key == approver) else {
+            return err!(ErrorCode::NotAnApprover);
+        };
+        let pending = &mut ctx.accounts.pending_action;
+        if Clock::get()?.unix_timestamp >= pending.expires_at {
+            return err!(ErrorCode::ActionExpired);
+        }
+        if pending.approvals & (1 << index) != 0 {
+            return err!(ErrorCode::AlreadyApproved);
+        }
+        pending.approvals |= 1 << index;
+        msg!("{:?} approved by {} ({}/{})", pending.action, approver, pending.approvals.count_ones(), pending.threshold);
+        Ok(())
+    }
+
+    /// The proposer or the task authority may withdraw a pending action at any time, and
+    /// anyone may clear one that has expired. The rent goes back to the proposer either way.
+    pub fn cancel_action(ctx: Context<CancelAction>) -> Result<()> {
+        let pending = &ctx.accounts.pending_action;
+        let signer = ctx.accounts.signer.key();
+        let expired = Clock::get()?.unix_timestamp >= pending.expires_at;
+        if !expired && signer != pending.proposer && signer != ctx.accounts.task_account.authority {
+            return err!(ErrorCode::UnauthorizedAction);
+        }
+        msg!("{:?} on task ID {} cancelled", pending.action, ctx.accounts.task_account.id);
+        Ok(())
+    }
+
+    /// Escrows `amount` lamports as a reward for completing the task. A bounty has a single
+    /// funder, who may top it up and push back, but not bring forward, its expiry. Archived
+    /// tasks cannot be funded, and a bounty expires within `MAX_BOUNTY_DURATION`.
+    pub fn fund_bounty(ctx: Context<FundBounty>, amount: u64, expires_at: i64) -> Result<()> {
+        if amount == 0 {
+            return err!(ErrorCode::InvalidBountyAmount);
+        }
+        check_bounty_terms(&ctx.accounts.task_account, expires_at)?;
+        let funder = ctx.accounts.funder.key();
+        let vault = &mut ctx.accounts.bounty_vault;
+        if vault.funder == Pubkey::default() {
+            vault.task = ctx.accounts.task_account.key();
+            vault.funder = funder;
+        } else if vault.funder != funder {
+            return err!(ErrorCode::BountyFunderMismatch);
+        } else if expires_at < vault.expires_at {
+            return err!(ErrorCode::BountyExpired);
+        }
+        vault.amount = vault.amount.checked_add(amount).ok_or(ErrorCode::InvalidBountyAmount)?;
+        vault.expires_at = expires_at;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.bounty_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        msg!("Task ID {}: bounty of {} lamports funded by {}", ctx.accounts.task_account.id, amount, funder);
+        Ok(())
+    }
+
+    /// Pays the bounty to the assignee of a Done task and returns the vault's rent to the funder.
+    pub fn complete_and_pay(ctx: Context<CompleteAndPay>) -> Result<()> {
+        let task = &ctx.accounts.task_account;
+        if task.status != TaskStatus::Done {
+            return err!(ErrorCode::TaskNotDone);
+        }
+        let vault = &ctx.accounts.bounty_vault;
+        if Clock::get()?.unix_timestamp >= vault.expires_at {
+            return err!(ErrorCode::BountyExpired);
+        }
+        let vault_info = vault.to_account_info();
+        **vault_info.try_borrow_mut_lamports()? -= vault.amount;
+        **ctx.accounts.assignee.try_borrow_mut_lamports()? += vault.amount;
+        msg!("Task ID {}: bounty of {} lamports paid to {}", task.id, vault.amount, ctx.accounts.assignee.key());
+        Ok(())
+    }
+
+    /// Returns an expired, unpaid bounty to its funder.
+    pub fn refund_bounty(ctx: Context<RefundBounty>) -> Result<()> {
+        let vault = &ctx.accounts.bounty_vault;
+        if Clock::get()?.unix_timestamp < vault.expires_at {
+            return err!(ErrorCode::BountyNotExpired);
+        }
+        msg!("Bounty of {} lamports refunded to {}", vault.amount, vault.funder);
+        Ok(())
+    }
+
+    /// Lets the task authority turn down a bounty, returning it to its funder.
+    pub fn reject_bounty(ctx: Context<RejectBounty>) -> Result<()> {
+        let vault = &ctx.accounts.bounty_vault;
+        msg!("Task ID {}: bounty of {} lamports rejected and returned to {}", ctx.accounts.task_account.id, vault.amount, vault.funder);
+        Ok(())
+    }
+
+    /// Like `fund_bounty`, escrowing `amount` of `mint` in the bounty's associated token
+    /// account. Works with both the Token and Token-2022 programs.
+    pub fn fund_token_bounty(ctx: Context<FundTokenBounty>, amount: u64, expires_at: i64) -> Result<()> {
+        if amount == 0 {
+            return err!(ErrorCode::InvalidBountyAmount);
+        }
+        check_bounty_terms(&ctx.accounts.task_account, expires_at)?;
+        let funder = ctx.accounts.funder.key();
+        let bounty = &mut ctx.accounts.token_bounty;
+        if bounty.funder == Pubkey::default() {
+            bounty.task = ctx.accounts.task_account.key();
+            bounty.funder = funder;
+            bounty.mint = ctx.accounts.mint.key();
+        } else if bounty.funder != funder {
+            return err!(ErrorCode::BountyFunderMismatch);
+        } else if bounty.mint != ctx.accounts.mint.key() {
+            return err!(ErrorCode::BountyMintMismatch);
+        } else if expires_at < bounty.expires_at {
+            return err!(ErrorCode::BountyExpired);
+        }
+        bounty.amount = bounty.amount.checked_add(amount).ok_or(ErrorCode::InvalidBountyAmount)?;
+        bounty.expires_at = expires_at;
+        let accounts = &ctx.accounts;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.funder_token_account.to_account_info(),
+                    mint: accounts.mint.to_account_info(),
+                    to: accounts.vault.to_account_info(),
+                    authority: accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+            accounts.mint.decimals,
+        )?;
+        msg!("Task ID {}: bounty of {} of mint {} funded by {}", accounts.task_account.id, amount, accounts.mint.key(), funder);
+        Ok(())
+    }
+
+    /// Like `complete_and_pay`, for a token bounty. The whole vault balance is paid out, which
+    /// can be less than the funded amount for Token-2022 mints with transfer fees.
+    pub fn complete_and_pay_token(ctx: Context<CompleteAndPayToken>) -> Result<()> {
+        let task = &ctx.accounts.task_account;
+        if task.status != TaskStatus::Done {
+            return err!(ErrorCode::TaskNotDone);
+        }
+        if Clock::get()?.unix_timestamp >= ctx.accounts.token_bounty.expires_at {
+            return err!(ErrorCode::BountyExpired);
+        }
+        let bump = ctx.bumps.token_bounty;
+        let accounts = &ctx.accounts;
+        let amount = accounts.vault.amount;
+        release_token_bounty(
+            &accounts.token_bounty,
+            &accounts.vault,
+            &accounts.mint,
+            &accounts.assignee_token_account.to_account_info(),
+            &accounts.funder,
+            &accounts.token_program,
+            bump,
+        )?;
+        msg!("Task ID {}: bounty of {} of mint {} paid to {}", task.id, amount, accounts.mint.key(), accounts.assignee_token_account.owner);
+        Ok(())
+    }
+
+    /// Returns an expired, unpaid token bounty to its funder. Unlike the task, the bounty
+    /// outlives deletion, so this does not need the task account.
+    pub fn refund_token_bounty(ctx: Context<RefundTokenBounty>) -> Result<()> {
+        if Clock::get()?.unix_timestamp < ctx.accounts.token_bounty.expires_at {
+            return err!(ErrorCode::BountyNotExpired);
+        }
+        let bump = ctx.bumps.token_bounty;
+        let accounts = &ctx.accounts;
+        let amount = accounts.vault.amount;
+        release_token_bounty(
+            &accounts.token_bounty,
+            &accounts.vault,
+            &accounts.mint,
+            &accounts.funder_token_account.to_account_info(),
+            &accounts.funder.to_account_info(),
+            &accounts.token_program,
+            bump,
+        )?;
+        msg!("Bounty of {} of mint {} refunded to {}", amount, accounts.mint.key(), accounts.funder.key());
+        Ok(())
+    }
+
+    /// Stakes `amount` lamports on finishing the task by its current `due_at`. The stake is
+    /// returned by `claim_stake` if the task is Done in time, and otherwise forfeit to the
+    /// config's current `stake_beneficiary` through `slash_stake`. What the beneficiary gets
+    /// must cover rent exemption, so slashing works even if its account does not exist yet.
+    pub fn commit_stake(ctx: Context<CommitStake>, amount: u64) -> Result<()> {
+        if amount - Stake::slash_tip(amount) < Rent::get()?.minimum_balance(0) {
+            return err!(ErrorCode::InvalidStakeAmount);
+        }
+        let task = &ctx.accounts.task_account;
+        let Some(deadline) = task.due_at else {
+            return err!(ErrorCode::NoDueDate);
+        };
+        if !task.status.is_active() || deadline <= Clock::get()?.unix_timestamp {
+            return err!(ErrorCode::DueDateInPast);
+        }
+        let staker = ctx.accounts.authority.key();
+        let beneficiary = ctx.accounts.config.stake_beneficiary;
+        if beneficiary == staker {
+            return err!(ErrorCode::InvalidBeneficiary);
+        }
+        let stake = &mut ctx.accounts.stake;
+        stake.task = task.key();
+        stake.staker = staker;
+        stake.beneficiary = beneficiary;
+        stake.amount = amount;
+        stake.deadline = deadline;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.stake.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        msg!("Task ID {}: {} lamports staked on finishing by {}", task.id, amount, deadline);
+        Ok(())
+    }
+
+    pub fn claim_stake(ctx: Context<ClaimStake>) -> Result<()> {
+        let task = &ctx.accounts.task_account;
+        let stake = &ctx.accounts.stake;
+        if !stake.is_kept(task) {
+            return err!(ErrorCode::StakeNotKept);
+        }
+        msg!("Task ID {}: stake of {} lamports returned to {}", task.id, stake.amount, stake.staker);
+        Ok(())
+    }
+
+    /// Callable by anyone once the deadline has passed without the task being Done. The
+    /// caller receives `SLASH_TIP_BPS` of the stake and the beneficiary the rest; a task
+    /// deleted before the deadline counts as missed.
+    pub fn slash_stake(ctx: Context<SlashStake>) -> Result<()> {
+        let stake = &ctx.accounts.stake;
+        if Clock::get()?.unix_timestamp <= stake.deadline {
+            return err!(ErrorCode::StakeNotDue);
+        }
+        let task_info = &ctx.accounts.task_account;
+        if task_info.owner == &ID && !task_info.data_is_empty() {
+            let task = TaskAccount::load(task_info)?;
+            if stake.is_kept(&task) {
+                return err!(ErrorCode::StakeKept);
+            }
+        }
+        let tip = Stake::slash_tip(stake.amount);
+        let stake_info = stake.to_account_info();
+        **stake_info.try_borrow_mut_lamports()? -= stake.amount;
+        **ctx.accounts.beneficiary.try_borrow_mut_lamports()? += stake.amount - tip;
+        **ctx.accounts.caller.try_borrow_mut_lamports()? += tip;
+        msg!("Stake of {} lamports on task {} slashed to {}", stake.amount, stake.task, stake.beneficiary);
+        Ok(())
+    }
+
+    pub fn is_task_overdue(ctx: Context<ViewTask>) -> Result<bool> {
+        Ok(ctx.accounts.task_account.is_overdue(Clock::get()?.unix_timestamp))
+    }
+
+    pub fn update_task(ctx: Context<UpdateTask>, update: TaskUpdate) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_RENAME)?;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(update.expected_revision)?;
+        if let Some(name) = update.name {
+            validate_name(&name, &ctx.accounts.config)?;
+            task.name = name;
+        }
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Task ID {} updated", task.id);
+        Ok(())
+    }
+
+    pub fn delete_task(ctx: Context<DeleteTask>) -> Result<()> {
+        ctx.accounts.authorize()?;
+        let accounts = &ctx.accounts;
+        consume_approval(&accounts.task_account, &accounts.pending_action, &accounts.proposer, ProposedAction::Delete)?;
+        if ctx.accounts.task_account.child_count > 0 {
+            return err!(ErrorCode::HasSubtasks);
+        }
+        remove_task(ctx.accounts)
+    }
+
+    /// Deletes a task together with its subtasks, passed as `remaining_accounts` in
+    /// `(subtask, description, checklist, bounty vault)` groups. Subtasks must not have children
+    /// of their own or a funded bounty.
+    pub fn delete_task_cascade<'info>(ctx: Context<'_, '_, 'info, 'info, DeleteTask<'info>>) -> Result<()> {
+        ctx.accounts.authorize()?;
+        let accounts = &ctx.accounts;
+        consume_approval(&accounts.task_account, &accounts.pending_action, &accounts.proposer, ProposedAction::Delete)?;
+        let authority = ctx.accounts.authority.to_account_info();
+        let parent_key = ctx.accounts.task_account.key();
+        for accounts in ctx.remaining_accounts.chunks(4) {
+            let [child_info, description_info, checklist_info, vault_info] = accounts else {
+                return err!(ErrorCode::SubtaskAccountsMismatch);
+            };
+            let child = current_task(child_info)?;
+            if child.parent != Some(parent_key) {
+                return err!(ErrorCode::ParentTaskMismatch);
+            }
+            if child.child_count > 0 {
+                return err!(ErrorCode::HasSubtasks);
+            }
+            if child.dependency_count > 0 {
+                return err!(ErrorCode::HasDependencies);
+            }
+            if child.approval_policy.is_some() {
+                return err!(ErrorCode::ApprovalRequired);
+            }
+            if description_info.key() != TaskDescription::address(&child_info.key()).0
+                || checklist_info.key() != Checklist::address(&child_info.key()).0
+                || vault_info.key() != BountyVault::address(&child_info.key()).0
+            {
+                return err!(ErrorCode::SubtaskAccountsMismatch);
+            }
+            if vault_info.owner == &ID {
+                return err!(ErrorCode::BountyFunded);
+            }
+            for info in [description_info, checklist_info] {
+                if info.owner == &ID {
+                    close_account(info, &authority)?;
+                }
+            }
+            let parent = &mut ctx.accounts.task_account;
+            parent.child_count -= 1;
+            if child.status.is_active() {
+                parent.open_child_count -= 1;
+            }
+            if let Some(project) = child.project_account(&mut ctx.accounts.project)? {
+                project.record_removal(child.status.is_active());
+            }
+            ctx.accounts.user_profile.record_removal(child.status.is_active());
+            msg!("Subtask ID {} deleted", child.id);
+            close_account(child_info, &authority)?;
+        }
+        if ctx.accounts.task_account.child_count > 0 {
+            return err!(ErrorCode::HasSubtasks);
+        }
+        remove_task(ctx.accounts)
+    }
+
+    pub fn add_checklist_item(
+        ctx: Context<AddChecklistItem>,
+        text: String,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        validate_label(&text, MAX_CHECKLIST_ITEM_LENGTH)?;
+        let checklist = &mut ctx.accounts.task_checklist;
+        checklist.task = ctx.accounts.task_account.key();
+        if checklist.items.len() >= MAX_CHECKLIST_ITEMS {
+            return err!(ErrorCode::ChecklistFull);
+        }
+        checklist.items.push(ChecklistItem { text, done: false });
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn edit_checklist_item(
+        ctx: Context<ManageChecklist>,
+        index: u8,
+        text: String,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        validate_label(&text, MAX_CHECKLIST_ITEM_LENGTH)?;
+        ctx.accounts.task_checklist.item_mut(index)?.text = text;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    /// Checking the last open item completes the task as Done when the checklist has
+    /// `auto_complete` set, the task is open and it has no open subtasks. A session key needs
+    /// `SESSION_UPDATE_STATUS` as well as `SESSION_EDIT` for the toggle that completes the task.
+    pub fn toggle_checklist_item(
+        ctx: Context<ToggleChecklistItem>,
+        index: u8,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let checklist = &mut ctx.accounts.task_checklist;
+        let item = checklist.item_mut(index)?;
+        item.done = !item.done;
+        let auto_complete = checklist.auto_complete && checklist.is_complete();
+        let now = Clock::get()?.unix_timestamp;
+        let accounts = &mut *ctx.accounts;
+        let task = &mut accounts.task_account;
+        task.check_revision(expected_revision)?;
+        if auto_complete && task.status.is_active() && task.open_child_count == 0 {
+            // Completing the task is a status change, which an edit-only session may not make.
+            task.check_signer(&accounts.authority, &accounts.session_key, SESSION_UPDATE_STATUS)?;
+            apply_status(
+                task,
+                &mut accounts.user_profile,
+                &mut accounts.parent_task,
+                &mut accounts.project,
+                TaskStatus::Done,
+                now,
+            )?;
+            msg!("Task ID {} auto-completed by its checklist", task.id);
+        } else {
+            task.touch(now);
+        }
+        Ok(())
+    }
+
+    /// Moves item `from` to position `to`, shifting the items in between.
+    pub fn move_checklist_item(
+        ctx: Context<ManageChecklist>,
+        from: u8,
+        to: u8,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let items = &mut ctx.accounts.task_checklist.items;
+        if from as usize >= items.len() || to as usize >= items.len() {
+            return err!(ErrorCode::ChecklistItemNotFound);
+        }
+        let item = items.remove(from as usize);
+        items.insert(to as usize, item);
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn remove_checklist_item(ctx: Context<ManageChecklist>, index: u8, expected_revision: Option<u64>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let checklist = &mut ctx.accounts.task_checklist;
+        checklist.item_mut(index)?;
+        checklist.items.remove(index as usize);
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn set_checklist_auto_complete(
+        ctx: Context<ManageChecklist>,
+        enabled: bool,
+        expected_revision: Option<u64>,
+    ) -> Result<()> {
+        let task = &ctx.accounts.task_account;
+        task.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        if enabled {
+            // Auto-complete later changes the task's status, so arming it takes that permission too.
+            task.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_UPDATE_STATUS)?;
+        }
+        ctx.accounts.task_checklist.auto_complete = enabled;
+        let task = &mut ctx.accounts.task_account;
+        task.check_revision(expected_revision)?;
+        task.touch(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    /// Percentage of checklist items that are done, rounded down.
+    pub fn checklist_completion(ctx: Context<ViewChecklist>) -> Result<u8> {
+        Ok(ctx.accounts.task_checklist.completion_percent())
+    }
+
+    pub fn init_task_description(ctx: Context<InitTaskDescription>, max_length: u32) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        if max_length as usize > MAX_DESCRIPTION_LENGTH {
+            return err!(ErrorCode::DescriptionTooLong);
+        }
+        let description = &mut ctx.accounts.task_description;
+        description.task = ctx.accounts.task_account.key();
+        description.max_length = max_length;
+        msg!("Description for task ID {} reserved {} bytes", ctx.accounts.task_account.id, max_length);
+        Ok(())
+    }
+
+    pub fn write_task_description(ctx: Context<WriteTaskDescription>, offset: u32, chunk: Vec<u8>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let description = &mut ctx.accounts.task_description;
+        if description.finalized {
+            return err!(ErrorCode::DescriptionFinalized);
+        }
+        // Chunks must be appended in order, so a retried transaction cannot duplicate content.
+        if offset as usize != description.content.len() {
+            return err!(ErrorCode::DescriptionOffsetMismatch);
+        }
+        if description.content.len() + chunk.len() > description.max_length as usize {
+            return err!(ErrorCode::DescriptionTooLong);
+        }
+        description.content.extend_from_slice(&chunk);
+        Ok(())
+    }
+
+    pub fn finalize_task_description(ctx: Context<WriteTaskDescription>) -> Result<()> {
+        ctx.accounts.task_account.check_signer(&ctx.accounts.authority, &ctx.accounts.session_key, SESSION_EDIT)?;
+        let description = &mut ctx.accounts.task_description;
+        if description.finalized {
+            return err!(ErrorCode::DescriptionFinalized);
+        }
+        if std::str::from_utf8(&description.content).is_err() {
+            return err!(ErrorCode::DescriptionNotUtf8);
+        }
+        description.finalized = true;
+        let task = &mut ctx.accounts.task_account;
+        task.description_hash = Some(hash(&description.content).to_bytes());
+        task.touch(Clock::get()?.unix_timestamp);
+        msg!("Description for task ID {} finalized at {} bytes", task.id, description.content.len());
+        Ok(())
+    }
+
+    pub fn migrate_task_seeds(ctx: Context<MigrateTaskSeeds>, id: u64) -> Result<()> {
+        let legacy_info = ctx.accounts.legacy_task_account.to_account_info();
+        let legacy = TaskAccount::load(&legacy_info)?;
+        if legacy.id != id {
+            return err!(ErrorCode::TaskIdMismatch);
+        }
+        if legacy.authority != ctx.accounts.authority.key() {
+            return err!(ErrorCode::UnauthorizedAction);
+        }
+        let new_id = migrated_task_id(id, &ctx.accounts.legacy_id_slot, &ctx.accounts.user_profile);
+        let task = &mut ctx.accounts.task_account;
+        task.set_inner(legacy);
+        let profile = &mut ctx.accounts.user_profile;
+        if new_id != id {
+            task.id = new_id;
+            task.rank = append_rank(&mut profile.last_rank, TaskAccount::initial_rank(new_id));
+        }
+        profile.authority = task.authority;
+        if task.id == profile.next_task_id {
+            profile.next_task_id = profile.next_task_id.checked_add(1).ok_or(ErrorCode::TaskIdOverflow)?;
+        }
+        profile.last_rank = profile.last_rank.max(task.rank);
+        if task.status.is_active() {
+            profile.active_task_count += 1;
+        } else {
+            profile.inactive_task_count += 1;
+        }
+        msg!("Legacy task ID {} migrated to ID {} at namespaced address {}", id, task.id, task.key());
+        close_account(&legacy_info, &ctx.accounts.authority.to_account_info())
+    }
+
+    /// Rewrites a task stored in an older layout into the current one, resizing the account
+    /// and settling the rent difference with the authority.
+    pub fn migrate_task(ctx: Context<MigrateTask>, id: u64) -> Result<()> {
+        let info = ctx.accounts.task_account.to_account_info();
+        let authority = ctx.accounts.authority.to_account_info();
+        if TaskAccount::layout_version(&info)? == Some(TaskAccount::VERSION) {
+            return err!(ErrorCode::TaskAlreadyMigrated);
+        }
+        let task = TaskAccount::load(&info)?;
+        if task.id != id {
+            return err!(ErrorCode::TaskIdMismatch);
+        }
+        if task.authority != authority.key() {
+            return err!(ErrorCode::UnauthorizedAction);
+        }
+        if info.key() != TaskAccount::address(&task.namespace(), task.id).0 {
+            return err!(anchor_lang::error::ErrorCode::ConstraintSeeds);
+        }
+
+        let new_len = TaskAccount::space(&task.name);
+        let required = Rent::get()?.minimum_balance(new_len);
+        let current = info.lamports();
+        if required > current {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer { from: authority.clone(), to: info.clone() },
+                ),
+                required - current,
+            )?;
+        } else {
+            **info.try_borrow_mut_lamports()? -= current - required;
+            **authority.try_borrow_mut_lamports()? += current - required;
+        }
+        info.resize(new_len)?;
+        let mut data = info.try_borrow_mut_data()?;
+        data.fill(0);
+        task.try_serialize(&mut &mut data[..])?;
+        msg!("Task ID {} migrated to layout version {}", task.id, TaskAccount::VERSION);
+        Ok(())
+    }
+
+    /// Creates the program's `Config`. Only the program's upgrade authority may do so, and
+    /// it becomes the first admin and stake beneficiary.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        max_name_length: u16,
+        task_fee: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.apply(ConfigUpdate {
+            paused: Some(false),
+            max_name_length: Some(max_name_length),
+            task_fee: Some(task_fee),
+            stake_beneficiary: Some(ctx.accounts.admin.key()),
+        })?;
+        msg!("Config initialized with admin {}", config.admin);
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<ManageConfig>, update: ConfigUpdate) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.apply(update)?;
+        msg!(
+            "Config updated: paused {}, max name length {}, task fee {}, stake beneficiary {}",
+            config.paused,
+            config.max_name_length,
+            config.task_fee,
+            config.stake_beneficiary
+        );
+        Ok(())
+    }
+
+    /// Sends accrued task fees, everything the config holds above rent, to the admin.
+    pub fn withdraw_fees(ctx: Context<ManageConfig>) -> Result<()> {
+        let info = ctx.accounts.config.to_account_info();
+        let fees = info.lamports() - Rent::get()?.minimum_balance(info.data_len());
+        **info.try_borrow_mut_lamports()? -= fees;
+        **ctx.accounts.admin.try_borrow_mut_lamports()? += fees;
+        msg!("{} lamports of fees withdrawn", fees);
+        Ok(())
+    }
+
+    /// First step of an admin rotation; `new_admin` takes over once it calls `accept_admin`.
+    pub fn propose_admin(ctx: Context<ManageConfig>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.config.pending_admin = Some(new_admin);
+        msg!("Admin rotation to {} proposed", new_admin);
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if config.pending_admin != Some(ctx.accounts.new_admin.key()) {
+            return err!(ErrorCode::UnauthorizedAction);
+        }
+        msg!("Admin rotated from {} to {}", config.admin, ctx.accounts.new_admin.key());
+        config.admin = ctx.accounts.new_admin.key();
+        config.pending_admin = None;
+        Ok(())
+    }
+}
+
+/// Legacy IDs came from one global space, so the profile counter may already have handed out
+/// a task's legacy ID, or not have reached it yet; either way the task gets the next free ID
+/// instead, so the counter never jumps ahead to a legacy ID.
+fn migrated_task_id(legacy_id: u64, legacy_id_slot: &AccountInfo, profile: &UserProfile) -> u64 {
+    if legacy_id < profile.next_task_id && legacy_id_slot.data_is_empty() {
+        legacy_id
+    } else {
+        profile.next_task_id
+    }
+}
+
+fn validate_name(name: &str, config: &Config) -> Result<()> {
+    validate_label(name, config.max_name_length as usize)
+}
+
+/// Labels are stored as Borsh strings, so the limit is on UTF-8 bytes, not characters.
+fn validate_label(name: &str, max_length: usize) -> Result<()> {
+    if name.is_empty() {
+        return err!(ErrorCode::NameEmpty);
+    }
+    if name.len() > max_length {
+        return err!(ErrorCode::NameTooLong);
+    }
+    if name.chars().any(char::is_control) {
+        return err!(ErrorCode::NameContainsControlCharacters);
+    }
+    Ok(())
+}
+
+fn validate_schedule(start_at: Option<i64>, due_at: Option<i64>, now: i64) -> Result<()> {
+    if let Some(due_at) = due_at {
+        if due_at <= now {
+            return err!(ErrorCode::DueDateInPast);
+        }
+        if start_at.is_some_and(|start_at| start_at > due_at) {
+            return err!(ErrorCode::StartAfterDue);
+        }
+    }
+    Ok(())
+}
+
+/// Fractional index strictly between two neighbouring ranks, or before `next` when there is
+/// no `prev`. Fails once two neighbours are adjacent integers; the client then has to spread
+/// the ranks out again.
+fn rank_between(prev: Option<u64>, next: u64) -> Result<u64> {
+    let rank = match prev {
+        Some(prev) if prev < next => prev + (next - prev) / 2,
+        None => next.saturating_sub(RANK_SPACING).max(next / 2),
+        Some(_) => return err!(ErrorCode::InvalidNeighbours),
+    };
+    if prev == Some(rank) || next == rank {
+        return err!(ErrorCode::RankGapExhausted);
+    }
+    Ok(rank)
+}
+
+/// Registry a tag instruction works on: the project's when one is passed, else the signer's.
+fn tag_namespace(project: &Option<Account<Project>>, authority: &Signer) -> Pubkey {
+    project.as_ref().map_or(authority.key(), |project| project.key())
+}
+
+/// Next rank at the end of a list: past both `floor` and every rank the list handed out
+/// before, which `last_rank` tracks.
+fn append_rank(last_rank: &mut u64, floor: u64) -> u64 {
+    let rank = last_rank.saturating_add(RANK_SPACING).max(floor);
+    *last_rank = rank;
+    rank
+}
+
+/// Role the `signer` acts on `task` with: `None` for the task authority itself, otherwise the
+/// role of `member`, which must be the signer's accepted membership in the authority's workspace.
+fn member_role(task: &TaskAccount, signer: &Pubkey, member: &Option<Account<Member>>) -> Result<Option<MemberRole>> {
+    if task.authority == *signer {
+        return Ok(None);
+    }
+    let Some(member) = member else {
+        return err!(ErrorCode::UnauthorizedAction);
+    };
+    if member.user != *signer || member.workspace != Workspace::address(&task.authority).0 {
+        return err!(ErrorCode::UnauthorizedAction);
+    }
+    if !member.accepted {
+        return err!(ErrorCode::InvitationPending);
+    }
+    Ok(Some(member.role))
+}
+
+/// Destructive actions on a task under an approval policy need a `PendingAction` for exactly
+/// that action which has met its threshold; the action consumes it, refunding its proposer.
+fn consume_approval<'info>(
+    task: &Account<'info, TaskAccount>,
+    pending_action: &Option<Account<'info, PendingAction>>,
+    proposer: &Option<UncheckedAccount<'info>>,
+    action: ProposedAction,
+) -> Result<()> {
+    if task.approval_policy.is_none() {
+        return Ok(());
+    }
+    let Some(pending) = pending_action else {
+        return err!(ErrorCode::ApprovalRequired);
+    };
+    if pending.task != task.key() || pending.action != action {
+        return err!(ErrorCode::PendingActionMismatch);
+    }
+    if Clock::get()?.unix_timestamp >= pending.expires_at {
+        return err!(ErrorCode::ActionExpired);
+    }
+    if pending.approvals.count_ones() < pending.threshold as u32 {
+        return err!(ErrorCode::ApprovalRequired);
+    }
+    let Some(proposer) = proposer.as_ref().filter(|proposer| proposer.key() == pending.proposer) else {
+        return err!(ErrorCode::UnauthorizedAction);
+    };
+    close_account(&pending.to_account_info(), proposer)
+}
+
+/// A bounty can only be funded on a task that is not archived, and for at most
+/// `MAX_BOUNTY_DURATION`.
+fn check_bounty_terms(task: &TaskAccount, expires_at: i64) -> Result<()> {
+    if task.status == TaskStatus::Archived {
+        return err!(ErrorCode::TaskArchived);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if expires_at <= now {
+        return err!(ErrorCode::BountyExpired);
+    }
+    if expires_at > now.saturating_add(MAX_BOUNTY_DURATION) {
+        return err!(ErrorCode::BountyExpiryTooFar);
+    }
+    Ok(())
+}
+
+/// Moves the task to `status`, keeping the profile, parent and project counters in step. A task
+/// cannot close while subtasks are open, and cannot reopen under a closed parent or project.
+fn apply_status<'info>(
+    task: &mut Account<'info, TaskAccount>,
+    profile: &mut UserProfile,
+    parent_task: &mut Option<Account<'info, TaskAccount>>,
+    project: &mut Option<Account<'info, Project>>,
+    status: TaskStatus,
+    now: i64,
+) -> Result<()> {
+    if !status.is_active() && task.open_child_count > 0 {
+        return err!(ErrorCode::OpenSubtasks);
+    }
+    if task.status.is_active() != status.is_active() {
+        if let Some(parent) = task.parent_account(parent_task)? {
+            if status.is_active() {
+                if !parent.status.is_active() {
+                    return err!(ErrorCode::ParentTaskClosed);
+                }
+                parent.open_child_count += 1;
+            } else {
+                parent.open_child_count -= 1;
+            }
+            parent.touch(now);
+        }
+        if let Some(project) = task.project_account(project)? {
+            if status.is_active() && project.closed {
+                return err!(ErrorCode::ProjectClosed);
+            }
+            project.record_status_change(status.is_active());
+        }
+        profile.record_status_change(status.is_active());
+    }
+    task.set_status(status, now);
+    task.touch(now);
+    Ok(())
+}
+
+/// Shared tail of `delete_task` and `delete_task_cascade`; the task itself is closed by Anchor.
+/// Its dependencies must be removed first so their accounts are not stranded.
+fn remove_task(accounts: &mut DeleteTask) -> Result<()> {
+    let task = &accounts.task_account;
+    if task.dependency_count > 0 {
+        return err!(ErrorCode::HasDependencies);
+    }
+    // Only a vault this program created counts; lamports anyone sent to the address do not.
+    if accounts.bounty_vault.owner == &ID {
+        return err!(ErrorCode::BountyFunded);
+    }
+    if let Some(parent) = task.parent_account(&mut accounts.parent_task)? {
+        parent.child_count -= 1;
+        if task.status.is_active() {
+            parent.open_child_count -= 1;
+        }
+    }
+    if let Some(project) = task.project_account(&mut accounts.project)? {
+        project.record_removal(task.status.is_active());
+    }
+    accounts.user_profile.record_removal(task.status.is_active());
+    let authority = accounts.authority.to_account_info();
+    for info in [accounts.task_description.to_account_info(), accounts.task_checklist.to_account_info()] {
+        if info.owner == &ID {
+            close_account(&info, &authority)?;
+        }
+    }
+    msg!("Task ID {} deleted by {}", task.id, accounts.authority_signer.key());
+    Ok(())
+}
+
+/// Fails unless `accounts` holds a `(TaskDependency, task)` pair for each of `task`'s
+/// dependencies and every depended-on task is closed.
+fn check_dependencies_closed<'info>(task: &Account<TaskAccount>, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+    let mut seen = BTreeSet::new();
+    for pair in accounts.chunks(2) {
+        let [dependency_info, blocker_info] = pair else {
+            return err!(ErrorCode::DependencyAccountsMismatch);
+        };
+        let dependency = Account::<TaskDependency>::try_from(dependency_info)?;
+        if dependency.task != task.key() || dependency.depends_on != blocker_info.key() {
+            return err!(ErrorCode::DependencyAccountsMismatch);
+        }
+        if !seen.insert(dependency.key()) {
+            return err!(ErrorCode::DependencyAccountsMismatch);
+        }
+        if current_task(blocker_info)?.status.is_active() {
+            return err!(ErrorCode::DependencyOpen);
+        }
+    }
+    if seen.len() != task.dependency_count as usize {
+        return err!(ErrorCode::DependencyAccountsMismatch);
+    }
+    Ok(())
+}
+
+/// Decodes a task passed in `remaining_accounts`, held to the same current-layout rule as the
+/// named task accounts.
+fn current_task<'info>(info: &'info AccountInfo<'info>) -> Result<Account<'info, TaskAccount>> {
+    let task = Account::<TaskAccount>::try_from(info)?;
+    if task.version != TaskAccount::VERSION {
+        return err!(ErrorCode::TaskNotMigrated);
+    }
+    Ok(task)
+}
+
+/// Rejects a new dependency of `task` on `depends_on` that would close a cycle, by walking
+/// everything `depends_on` transitively depends on, up to `MAX_DEPENDENCY_DEPTH` hops.
+/// `accounts` holds the tasks and `TaskDependency` accounts of that subgraph in any order;
+/// each task's `dependency_count` proves that none of its edges were left out.
+fn check_dependency_cycle<'info>(
+    task: Pubkey,
+    depends_on: &Account<TaskAccount>,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    if depends_on.key() == task {
+        return err!(ErrorCode::DependencyCycle);
+    }
+    let mut tasks = Vec::new();
+    let mut edges = BTreeSet::new();
+    for info in accounts {
+        match TaskAccount::layout_version(info) {
+            Ok(_) => tasks.push((info.key(), current_task(info)?.dependency_count)),
+            Err(_) => {
+                let dependency = Account::<TaskDependency>::try_from(info)?;
+                edges.insert((dependency.task, dependency.depends_on));
+            }
+        }
+    }
+    let dependency_count = |key: Pubkey| -> Result<u32> {
+        match tasks.iter().find(|(k, _)| *k == key) {
+            Some((_, count)) => Ok(*count),
+            None => err!(ErrorCode::DependencyAccountsMismatch),
+        }
+    };
+
+    let mut visited = BTreeSet::new();
+    let mut frontier = vec![(depends_on.key(), depends_on.dependency_count)];
+    for _ in 0..MAX_DEPENDENCY_DEPTH {
+        let mut next = Vec::new();
+        for (node, count) in frontier {
+            if !visited.insert(node) {
+                continue;
+            }
+            let targets: Vec<Pubkey> = edges
+                .range((node, Pubkey::default())..)
+                .take_while(|(from, _)| *from == node)
+                .map(|(_, to)| *to)
+                .collect();
+            if targets.len() != count as usize {
+                return err!(ErrorCode::DependencyAccountsMismatch);
+            }
+            for target in targets {
+                if target == task {
+                    return err!(ErrorCode::DependencyCycle);
+                }
+                next.push((target, dependency_count(target)?));
+            }
+        }
+        if next.is_empty() {
+            return Ok(());
+        }
+        frontier = next;
+    }
+    err!(ErrorCode::DependencyChainTooDeep)
+}
+
+/// Empties a token bounty's vault into `destination` and closes it, refunding its rent to `funder`.
+/// Transfer fees Token-2022 withheld in the vault are harvested to the mint first, since the
+/// vault cannot be closed while it holds any.
+fn release_token_bounty<'info>(
+    bounty: &Account<'info, TokenBounty>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: &AccountInfo<'info>,
+    funder: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    bump: u8,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[TOKEN_BOUNTY_SEED, bounty.task.as_ref(), &[bump]]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: destination.clone(),
+                authority: bounty.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        vault.amount,
+        mint.decimals,
+    )?;
+    if withheld_transfer_fees(&vault.to_account_info())? > 0 {
+        transfer_fee::harvest_withheld_tokens_to_mint(
+            CpiContext::new(
+                token_program.to_account_info(),
+                HarvestWithheldTokensToMint {
+                    token_program_id: token_program.to_account_info(),
+                    mint: mint.to_account_info(),
+                },
+            ),
+            vec![vault.to_account_info()],
+        )?;
+    }
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination: funder.clone(),
+            authority: bounty.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
+
+/// Transfer fees withheld in a Token-2022 account; always zero for the Token program.
+fn withheld_transfer_fees(token_account: &AccountInfo) -> Result<u64> {
+    if token_account.owner != &spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = token_account.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(state.get_extension::<TransferFeeAmount>().map_or(0, |fee| u64::from(fee.withheld_amount)))
+}
+
+fn close_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? += lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Fixed-size fields come first so they sit at the stable `TASK_*_OFFSET`s that
+/// `getProgramAccounts` memcmp filters rely on; variable-length data follows. New fields are
+/// only ever appended, each with a `VERSION` bump and a step in `TaskAccount::decode`. Handlers
+/// only accept the current version; older accounts go through `migrate_task` first.
+#[account(discriminator = TASK_DISCRIMINATOR)]
+#[derive(Default)]
+pub struct TaskAccount {
+    /// Layout version, see `TaskAccount::VERSION`.
+    pub version: u8,
+    pub id: u64,
+    pub authority: Pubkey,
+    pub status: TaskStatus,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Incremented on every mutation, for optimistic concurrency via `expected_revision`.
+    pub revision: u64,
+    pub start_at: Option<i64>,
+    pub due_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    /// SHA-256 of the finalized `TaskDescription` content.
+    pub description_hash: Option<[u8; 32]>,
+    pub name: String,
+    pub priority: TaskPriority,
+    /// Fractional ordering key among the authority's tasks; lower sorts first.
+    pub rank: u64,
+    /// Bit `i` is set when the task carries tag `i` of the `TagRegistry` of its `namespace`.
+    pub tags: u32,
+    pub parent: Option<Pubkey>,
+    pub child_count: u32,
+    /// Children that are neither Done nor Archived.
+    pub open_child_count: u32,
+    /// Number of `TaskDependency` accounts with this task as `task`.
+    pub dependency_count: u32,
+    /// Project the task belongs to; its ID and address are then scoped to the project.
+    pub project: Option<Pubkey>,
+    pub assignee: Option<Pubkey>,
+    /// Further assignees, at most `MAX_CO_ASSIGNEES`; only set while `assignee` is.
+    pub co_assignees: Vec<Pubkey>,
+    /// Authority the task was created by. Its address stays derived from this key after the
+    /// task is transferred, see `TaskAccount::namespace`.
+    pub creator: Pubkey,
+    /// Set by `propose_transfer` until the transfer is accepted or cancelled.
+    pub pending_owner: Option<Pubkey>,
+    pub transfer_expires_at_slot: Option<u64>,
+    /// `ApprovalPolicy` that destructive actions on the task need sign-off from.
+    pub approval_policy: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TaskPriority {
+    #[default]
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TaskStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Blocked,
+    InReview,
+    Done,
+    Archived,
+}
+
+impl TaskStatus {
+    pub fn is_active(self) -> bool {
+        !matches!(self, TaskStatus::Done | TaskStatus::Archived)
+    }
+
+    pub fn can_transition_to(self, next: TaskStatus) -> bool {
+        use TaskStatus::*;
+        matches!(
+            (self, next),
+            (Todo, InProgress)
+                | (Todo, Archived)
+                | (InProgress, Todo)
+                | (InProgress, Blocked)
+                | (InProgress, InReview)
+                | (Blocked, InProgress)
+                | (InReview, InProgress)
+                | (InReview, Done)
+                | (Done, InProgress)
+                | (Done, Archived)
+        )
+    }
+}
+
+/// Fields left as `None` keep their current value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigUpdate {
+    pub paused: Option<bool>,
+    pub max_name_length: Option<u16>,
+    pub task_fee: Option<u64>,
+    pub stake_beneficiary: Option<Pubkey>,
+}
+
+/// Fields left as `None` keep their current value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct TaskUpdate {
+    pub name: Option<String>,
+    pub expected_revision: Option<u64>,
+}
+
+/// Unversioned layout the program shipped with, stored under `LEGACY_TASK_DISCRIMINATOR`.
+#[derive(AnchorDeserialize)]
+pub struct LegacyTaskAccount {
+    pub id: u64,
+    pub name: String,
+    pub authority: Pubkey,
+    pub active: bool,
+}
+
+impl LegacyTaskAccount {
+    /// The legacy layout never recorded timestamps, so the migration time stands in for them.
+    fn upgrade(self, now: i64) -> TaskAccount {
+        TaskAccount {
+            version: TaskAccount::VERSION,
+            id: self.id,
+            authority: self.authority,
+            status: if self.active { TaskStatus::Todo } else { TaskStatus::Done },
+            created_at: now,
+            updated_at: now,
+            name: self.name,
+            rank: TaskAccount::initial_rank(self.id),
+            creator: self.authority,
+            ..Default::default()
+        }
+    }
+}
+
+/// Version 1 layout, which kept the variable-length `name` ahead of `authority`.
+#[derive(AnchorDeserialize)]
+pub struct TaskAccountV1 {
+    pub version: u8,
+    pub id: u64,
+    pub name: String,
+    pub authority: Pubkey,
+    pub status: TaskStatus,
+    pub description_hash: Option<[u8; 32]>,
+    pub start_at: Option<i64>,
+    pub due_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub revision: u64,
+}
+
+impl TaskAccountV1 {
+    fn upgrade(self) -> TaskAccount {
+        TaskAccount {
+            version: TaskAccount::VERSION,
+            id: self.id,
+            authority: self.authority,
+            status: self.status,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            revision: self.revision,
+            start_at: self.start_at,
+            due_at: self.due_at,
+            completed_at: self.completed_at,
+            description_hash: self.description_hash,
+            name: self.name,
+            rank: TaskAccount::initial_rank(self.id),
+            creator: self.authority,
+            ..Default::default()
+        }
+    }
+}
+
+/// `task` cannot move to InProgress while `depends_on` is still open.
+#[account]
+pub struct TaskDependency {
+    pub task: Pubkey,
+    pub depends_on: Pubkey,
+}
+
+#[event]
+pub struct TaskAssigned {
+    pub task: Pubkey,
+    pub assignee: Pubkey,
+    pub assigned_by: Pubkey,
+}
+
+#[event]
+pub struct TaskUnassigned {
+    pub task: Pubkey,
+    pub assignee: Pubkey,
+    pub unassigned_by: Pubkey,
+}
+
+/// A hot key the authority lets act on its tasks, within `permissions` and until `expires_at`.
+/// Deleting, transferring, assigning and migrating tasks always need the authority itself.
+#[account]
+pub struct SessionKey {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    /// Combination of the `SESSION_*` flags.
+    pub permissions: u8,
+}
+
+/// A task authority's team: members may act on the owner's tasks according to their role.
+#[account]
+pub struct Workspace {
+    pub owner: Pubkey,
+    pub name: String,
+    pub member_count: u32,
+}
+
+#[account]
+pub struct Member {
+    pub workspace: Pubkey,
+    pub user: Pubkey,
+    pub role: MemberRole,
+    /// Set once `user` accepts the invitation; until then the membership grants nothing.
+    pub accepted: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemberRole {
+    Owner,
+    Admin,
+    Editor,
+    Viewer,
+}
+
+impl MemberRole {
+    fn check_can_edit(self) -> Result<()> {
+        match self {
+            MemberRole::Viewer => err!(ErrorCode::ViewerUnauthorized),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deleting and assigning tasks is reserved for owners and admins.
+    fn check_is_admin(self) -> Result<()> {
+        match self {
+            MemberRole::Owner | MemberRole::Admin => Ok(()),
+            MemberRole::Editor => err!(ErrorCode::EditorUnauthorized),
+            MemberRole::Viewer => err!(ErrorCode::ViewerUnauthorized),
+        }
+    }
+
+    /// Owners manage every role, admins only editors and viewers.
+    fn check_can_manage(self, role: MemberRole) -> Result<()> {
+        match (self, role) {
+            (MemberRole::Owner, _) | (MemberRole::Admin, MemberRole::Editor | MemberRole::Viewer) => Ok(()),
+            (MemberRole::Admin, _) => err!(ErrorCode::AdminUnauthorized),
+            (MemberRole::Editor, _) => err!(ErrorCode::EditorUnauthorized),
+            (MemberRole::Viewer, _) => err!(ErrorCode::ViewerUnauthorized),
+        }
+    }
+}
+
+/// `threshold` of `approvers` must sign off on destructive actions for the task or project
+/// at `scope`.
+#[account]
+pub struct ApprovalPolicy {
+    pub scope: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+/// A destructive action awaiting sign-off under the task's `ApprovalPolicy`.
+#[account]
+pub struct PendingAction {
+    pub task: Pubkey,
+    pub policy: Pubkey,
+    pub action: ProposedAction,
+    pub proposer: Pubkey,
+    pub threshold: u8,
+    /// Bit `i` is set once `policy.approvers[i]` has approved.
+    pub approvals: u8,
+    pub expires_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProposedAction {
+    Delete,
+    Archive,
+    Transfer { new_owner: Pubkey },
+}
+
+/// Escrow for a task's bounty. Its balance above rent is the reward, `amount`.
+#[account]
+pub struct BountyVault {
+    pub task: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+/// A task's bounty in `mint`, escrowed in the associated token account this PDA owns.
+#[account]
+pub struct TokenBounty {
+    pub task: Pubkey,
+    pub funder: Pubkey,
+    pub mint: Pubkey,
+    /// Total funded; the vault may hold less if the mint charges transfer fees.
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+/// Lamports staked by `staker` on finishing `task` by `deadline`, held on top of rent.
+#[account]
+pub struct Stake {
+    pub task: Pubkey,
+    pub staker: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    /// The task's `due_at` when the stake was committed.
+    pub deadline: i64,
+}
+
+/// Program-wide settings, a singleton at `[CONFIG_SEED]`. Every instruction other than those
+/// managing the config fails while `paused` is set. Task fees accrue on the account itself.
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Set by `propose_admin` until the proposed admin accepts.
+    pub pending_admin: Option<Pubkey>,
+    pub paused: bool,
+    /// In UTF-8 bytes, at most `MAX_NAME_LENGTH_LIMIT`.
+    pub max_name_length: u16,
+    /// Lamports charged for each task created.
+    pub task_fee: u64,
+    /// Receives stakes committed from now on if they are slashed.
+    pub stake_beneficiary: Pubkey,
+}
+
+/// Groups tasks under their own ID counter.
+#[account]
+pub struct Project {
+    pub owner: Pubkey,
+    pub name: String,
+    pub next_task_id: u64,
+    pub open_task_count: u64,
+    pub closed_task_count: u64,
+    pub closed: bool,
+    /// Policy new tasks in the project are put under, see `create_project_approval_policy`.
+    pub approval_policy: Option<Pubkey>,
+    /// Highest rank handed out to a task in the project, see `append_rank`.
+    pub last_rank: u64,
+}
+
+/// Lightweight steps of a task that do not warrant their own `TaskAccount`s.
+#[account]
+pub struct Checklist {
+    pub task: Pubkey,
+    /// Complete the task once every item is checked, see `toggle_checklist_item`.
+    pub auto_complete: bool,
+    pub items: Vec<ChecklistItem>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+/// Long-form description, written in chunks and then frozen by finalizing it.
+#[account]
+pub struct TaskDescription {
+    pub task: Pubkey,
+    pub max_length: u32,
+    pub finalized: bool,
+    pub content: Vec<u8>,
+}
+
+/// Tags are addressed by their index, which is also their bit in `TaskAccount::tags`.
+/// Indices are never reused, so retiring a tag cannot change what a task's bits mean.
+/// There is one registry per task namespace, so tags stay valid when a task changes hands.
+#[account]
+pub struct TagRegistry {
+    /// The project, or else the user, whose tasks draw their tags from this registry.
+    pub namespace: Pubkey,
+    pub tags: Vec<Tag>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Tag {
+    pub name: String,
+    pub retired: bool,
+}
+
+#[account]
+pub struct UserProfile {
+    pub authority: Pubkey,
+    pub next_task_id: u64,
+    pub active_task_count: u64,
+    pub inactive_task_count: u64,
+    /// Highest rank handed out to a task outside any project, see `append_rank`.
+    pub last_rank: u64,
+}
+
+/// Upper bound for `Config::max_name_length`, which is what names are checked against.
+const MAX_NAME_LENGTH_LIMIT: usize = 128;
+const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+const RANK_SPACING: u64 = 1 << 32;
+const MAX_TAGS: usize = 32;
+const MAX_TAG_NAME_LENGTH: usize = 16;
+const MAX_DEPENDENCY_DEPTH: usize = 8;
+const MAX_CHECKLIST_ITEMS: usize = 20;
+const MAX_WORKSPACE_NAME_LENGTH: usize = 32;
+const MAX_CO_ASSIGNEES: usize = 3;
+/// Bounded by the width of `PendingAction::approvals`.
+const MAX_APPROVERS: usize = 8;
+/// Project names are part of the project's seeds, which are limited to 32 bytes each.
+const MAX_PROJECT_NAME_LENGTH: usize = 32;
+const MAX_CHECKLIST_ITEM_LENGTH: usize = 64;
+/// Longest a bounty may stay in escrow, in seconds; a funded bounty blocks deleting its task.
+const MAX_BOUNTY_DURATION: i64 = 365 * 24 * 60 * 60;
+const DISCRIMINATOR_LENGTH: usize = 8;
+const U64_LENGTH: usize = 8;
+const STRING_PREFIX_LENGTH: usize = 4;
+const PUBLIC_KEY_LENGTH: usize = 32;
+const ENUM_LENGTH: usize = 1;
+const U8_LENGTH: usize = 1;
+const U16_LENGTH: usize = 2;
+const U32_LENGTH: usize = 4;
+const I64_LENGTH: usize = 8;
+const BOOL_LENGTH: usize = 1;
+const OPTION_PREFIX_LENGTH: usize = 1;
+const HASH_LENGTH: usize = 32;
+
+/// Discriminator of task accounts written before the layout carried a version byte.
+pub const LEGACY_TASK_DISCRIMINATOR: &[u8] = &[235, 32, 10, 23, 81, 60, 170, 203];
+/// First 8 bytes of `sha256("account:TaskAccount:versioned")`. Versioned tasks use their own
+/// discriminator so that a legacy account can never be misread as a versioned one.
+pub const TASK_DISCRIMINATOR: &[u8] = &[247, 14, 56, 199, 79, 37, 95, 237];
+
+// Byte offsets of the fixed-size `TaskAccount` fields, including the discriminator.
+// Stable from layout version 2 onwards.
+#[constant]
+pub const TASK_VERSION_OFFSET: u32 = 8;
+#[constant]
+pub const TASK_ID_OFFSET: u32 = 9;
+#[constant]
+pub const TASK_AUTHORITY_OFFSET: u32 = 17;
+#[constant]
+pub const TASK_STATUS_OFFSET: u32 = 49;
+#[constant]
+pub const TASK_CREATED_AT_OFFSET: u32 = 50;
+#[constant]
+pub const TASK_UPDATED_AT_OFFSET: u32 = 58;
+#[constant]
+pub const TASK_REVISION_OFFSET: u32 = 66;
+
+/// Lets a session key change task status.
+#[constant]
+pub const SESSION_UPDATE_STATUS: u8 = 1 << 0;
+/// Lets a session key rename tasks.
+#[constant]
+pub const SESSION_RENAME: u8 = 1 << 1;
+/// Lets a session key edit everything else about a task: priority, order, tags, dependencies,
+/// checklist and description.
+#[constant]
+pub const SESSION_EDIT: u8 = 1 << 2;
+const SESSION_ALL: u8 = SESSION_UPDATE_STATUS | SESSION_RENAME | SESSION_EDIT;
+
+const _: () = assert!(
+    TASK_REVISION_OFFSET as usize + U64_LENGTH
+        == DISCRIMINATOR_LENGTH + U8_LENGTH + U64_LENGTH + PUBLIC_KEY_LENGTH + ENUM_LENGTH + I64_LENGTH * 2 + U64_LENGTH
+);
+
+#[constant]
+pub const TASK_SEED: &[u8] = b"task";
+#[constant]
+pub const LEGACY_TASK_SEED: &[u8] = b"task_seed";
+#[constant]
+pub const PROFILE_SEED: &[u8] = b"profile";
+#[constant]
+pub const DESCRIPTION_SEED: &[u8] = b"description";
+#[constant]
+pub const TAG_REGISTRY_SEED: &[u8] = b"tags";
+#[constant]
+pub const DEPENDENCY_SEED: &[u8] = b"dependency";
+#[constant]
+pub const CHECKLIST_SEED: &[u8] = b"checklist";
+#[constant]
+pub const PROJECT_SEED: &[u8] = b"project";
+#[constant]
+pub const SESSION_SEED: &[u8] = b"session";
+#[constant]
+pub const APPROVAL_POLICY_SEED: &[u8] = b"approval_policy";
+#[constant]
+pub const PENDING_ACTION_SEED: &[u8] = b"pending_action";
+#[constant]
+pub const BOUNTY_SEED: &[u8] = b"bounty";
+#[constant]
+pub const TOKEN_BOUNTY_SEED: &[u8] = b"token_bounty";
+#[constant]
+pub const STAKE_SEED: &[u8] = b"stake";
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+/// Share of a slashed stake, in basis points, paid to whoever calls `slash_stake`.
+#[constant]
+pub const SLASH_TIP_BPS: u64 = 100;
+#[constant]
+pub const WORKSPACE_SEED: &[u8] = b"workspace";
+#[constant]
+pub const MEMBER_SEED: &[u8] = b"member";
+
+impl TaskAccount {
+    pub const VERSION: u8 = 10;
+
+    pub const LEN: usize = DISCRIMINATOR_LENGTH 
+                         + U8_LENGTH
+                         + U64_LENGTH 
+                         + PUBLIC_KEY_LENGTH
+                         + ENUM_LENGTH
+                         + I64_LENGTH
+                         + I64_LENGTH
+                         + U64_LENGTH
+                         + (OPTION_PREFIX_LENGTH + I64_LENGTH) * 3
+                         + (OPTION_PREFIX_LENGTH + HASH_LENGTH)
+                         + (STRING_PREFIX_LENGTH + MAX_NAME_LENGTH_LIMIT)
+                         + ENUM_LENGTH
+                         + U64_LENGTH
+                         + U32_LENGTH
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH)
+                         + U32_LENGTH
+                         + U32_LENGTH
+                         + U32_LENGTH
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH)
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH)
+                         + (U32_LENGTH + MAX_CO_ASSIGNEES * PUBLIC_KEY_LENGTH)
+                         + PUBLIC_KEY_LENGTH
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH)
+                         + (OPTION_PREFIX_LENGTH + U64_LENGTH)
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH);
+
+    /// The parent passed alongside this task, which must be present exactly when it has one.
+    fn parent_account<'a, 'info>(
+        &self,
+        parent: &'a mut Option<Account<'info, TaskAccount>>,
+    ) -> Result<Option<&'a mut Account<'info, TaskAccount>>> {
+        match (self.parent, parent.as_mut()) {
+            (None, _) => Ok(None),
+            (Some(key), Some(parent)) if parent.key() == key => Ok(Some(parent)),
+            (Some(_), _) => err!(ErrorCode::ParentTaskMismatch),
+        }
+    }
+
+    /// The project passed alongside this task, which must be present exactly when it has one.
+    fn project_account<'a, 'info>(
+        &self,
+        project: &'a mut Option<Account<'info, Project>>,
+    ) -> Result<Option<&'a mut Account<'info, Project>>> {
+        match (self.project, project.as_mut()) {
+            (None, _) => Ok(None),
+            (Some(key), Some(project)) if project.key() == key => Ok(Some(project)),
+            (Some(_), _) => err!(ErrorCode::ProjectMismatch),
+        }
+    }
+
+    /// Fails unless `signer` is the task authority, or a session key it delegated that is still
+    /// valid and grants `permission`.
+    fn check_signer(&self, signer: &Signer, session_key: &Option<Account<SessionKey>>, permission: u8) -> Result<()> {
+        if signer.key() == self.authority {
+            return Ok(());
+        }
+        match session_key {
+            Some(session) if session.authority == self.authority && session.session_key == signer.key() => {
+                session.check(permission, Clock::get()?.unix_timestamp)
+            }
+            _ => err!(ErrorCode::UnauthorizedAction),
+        }
+    }
+
+    pub fn is_assignee(&self, user: &Pubkey) -> bool {
+        self.assignee.as_ref() == Some(user) || self.co_assignees.contains(user)
+    }
+
+    /// Key the task's ID is scoped to: its project, or else the authority that created it.
+    pub fn namespace(&self) -> Pubkey {
+        self.project.unwrap_or(self.creator)
+    }
+
+    /// New tasks go to the end of the list, leaving room to insert between them.
+    pub fn initial_rank(id: u64) -> u64 {
+        id.saturating_add(1).saturating_mul(RANK_SPACING)
+    }
+
+    /// Space needed to store a task named `name`; `LEN` is the size for the longest name.
+    pub fn space(name: &str) -> usize {
+        Self::LEN - MAX_NAME_LENGTH_LIMIT + name.len()
+    }
+
+    /// Version of the layout stored in `info`, or `None` for the unversioned legacy layout.
+    pub fn layout_version(info: &AccountInfo) -> Result<Option<u8>> {
+        if info.owner != &ID {
+            return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+        }
+        let data = info.try_borrow_data()?;
+        if data.len() <= DISCRIMINATOR_LENGTH {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorNotFound);
+        }
+        match &data[..DISCRIMINATOR_LENGTH] {
+            d if d == LEGACY_TASK_DISCRIMINATOR => Ok(None),
+            d if d == TASK_DISCRIMINATOR => Ok(Some(data[DISCRIMINATOR_LENGTH])),
+            _ => err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch),
+        }
+    }
+
+    /// Reads a task stored in any layout this program has written, upgraded in memory to the
+    /// current one. Use `migrate_task` to persist the upgrade.
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        let version = Self::layout_version(info)?;
+        let data = info.try_borrow_data()?;
+        let mut body = &data[DISCRIMINATOR_LENGTH..];
+        match version {
+            None => Ok(LegacyTaskAccount::deserialize(&mut body)?.upgrade(Clock::get()?.unix_timestamp)),
+            Some(1) => Ok(TaskAccountV1::deserialize(&mut body)?.upgrade()),
+            Some(version) if (2..=Self::VERSION).contains(&version) => Self::decode(version, &mut body),
+            Some(_) => err!(ErrorCode::UnsupportedLayoutVersion),
+        }
+    }
+
+    /// Decodes a layout from version 2 on. Fields are only appended from there, but resizing
+    /// leaves stale bytes past the end of the data, so only the fields `version` had are read
+    /// and the rest keep their defaults.
+    fn decode(version: u8, body: &mut &[u8]) -> Result<Self> {
+        fn read<T: AnchorDeserialize>(body: &mut &[u8]) -> Result<T> {
+            Ok(T::deserialize(body)?)
+        }
+        let mut task = Self {
+            version: read(body)?,
+            id: read(body)?,
+            authority: read(body)?,
+            status: read(body)?,
+            created_at: read(body)?,
+            updated_at: read(body)?,
+            revision: read(body)?,
+            start_at: read(body)?,
+            due_at: read(body)?,
+            completed_at: read(body)?,
+            description_hash: read(body)?,
+            name: read(body)?,
+            ..Default::default()
+        };
+        if version >= 3 {
+            task.priority = read(body)?;
+            task.rank = read(body)?;
+        } else {
+            task.rank = Self::initial_rank(task.id);
+        }
+        if version >= 4 {
+            task.tags = read(body)?;
+        }
+        if version >= 5 {
+            task.parent = read(body)?;
+            task.child_count = read(body)?;
+            task.open_child_count = read(body)?;
+        }
+        if version >= 6 {
+            task.dependency_count = read(body)?;
+        }
+        if version >= 7 {
+            task.project = read(body)?;
+        }
+        if version >= 8 {
+            task.assignee = read(body)?;
+            task.co_assignees = read(body)?;
+        }
+        if version >= 9 {
+            task.creator = read(body)?;
+            task.pending_owner = read(body)?;
+            task.transfer_expires_at_slot = read(body)?;
+        } else {
+            task.creator = task.authority;
+        }
+        if version >= 10 {
+            task.approval_policy = read(body)?;
+        }
+        task.version = Self::VERSION;
+        Ok(task)
+    }
+
+    /// Moves to `status`, stamping `completed_at` on Done and clearing it when reopened.
+    fn set_status(&mut self, status: TaskStatus, now: i64) {
+        if status == TaskStatus::Done {
+            self.completed_at = Some(now);
+        } else if status.is_active() {
+            self.completed_at = None;
+        }
+        self.status = status;
+    }
+
+    fn touch(&mut self, now: i64) {
+        self.updated_at = now;
+        self.revision += 1;
+    }
+
+    fn check_revision(&self, expected_revision: Option<u64>) -> Result<()> {
+        match expected_revision {
+            Some(expected) if expected != self.revision => err!(ErrorCode::StaleRevision),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_overdue(&self, now: i64) -> bool {
+        self.status.is_active() && self.due_at.is_some_and(|due_at| now > due_at)
+    }
+
+    /// Address of task `id` in `namespace`, see `TaskAccount::namespace`.
+    pub fn address(namespace: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TASK_SEED, namespace.as_ref(), &id.to_le_bytes()], &ID)
+    }
+
+    /// Address of task `id` under the old global seed scheme, only needed to migrate.
+    pub fn legacy_address(id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[LEGACY_TASK_SEED, &id.to_le_bytes()], &ID)
+    }
+}
+
+impl TaskDescription {
+    pub fn space(max_length: u32) -> usize {
+        DISCRIMINATOR_LENGTH
+            + PUBLIC_KEY_LENGTH
+            + U32_LENGTH
+            + BOOL_LENGTH
+            + (U32_LENGTH + max_length as usize)
+    }
+
+    pub fn address(task: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[DESCRIPTION_SEED, task.as_ref()], &ID)
+    }
+}
+
+impl TaskDependency {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH;
+
+    pub fn address(task: &Pubkey, depends_on: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[DEPENDENCY_SEED, task.as_ref(), depends_on.as_ref()], &ID)
+    }
+}
+
+impl SessionKey {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + I64_LENGTH
+                         + U8_LENGTH;
+
+    pub fn address(authority: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SESSION_SEED, authority.as_ref(), session_key.as_ref()], &ID)
+    }
+
+    fn check(&self, permission: u8, now: i64) -> Result<()> {
+        if now >= self.expires_at {
+            return err!(ErrorCode::SessionExpired);
+        }
+        if self.permissions & permission == 0 {
+            return err!(ErrorCode::SessionPermissionDenied);
+        }
+        Ok(())
+    }
+}
+
+impl ApprovalPolicy {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + (U32_LENGTH + MAX_APPROVERS * PUBLIC_KEY_LENGTH)
+                         + U8_LENGTH;
+
+    pub fn address(scope: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[APPROVAL_POLICY_SEED, scope.as_ref()], &ID)
+    }
+
+    fn init(&mut self, scope: Pubkey, approvers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let unique = approvers.iter().enumerate().all(|(i, key)| !approvers[..i].contains(key));
+        if approvers.len() > MAX_APPROVERS || !unique || threshold == 0 || threshold as usize > approvers.len() {
+            return err!(ErrorCode::InvalidApprovalPolicy);
+        }
+        self.scope = scope;
+        self.approvers = approvers;
+        self.threshold = threshold;
+        Ok(())
+    }
+}
+
+impl BountyVault {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + U64_LENGTH
+                         + I64_LENGTH;
+
+    pub fn address(task: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[BOUNTY_SEED, task.as_ref()], &ID)
+    }
+}
+
+impl TokenBounty {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + U64_LENGTH
+                         + I64_LENGTH;
+
+    pub fn address(task: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TOKEN_BOUNTY_SEED, task.as_ref()], &ID)
+    }
+
+}
+
+impl Stake {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + U64_LENGTH
+                         + I64_LENGTH;
+
+    pub fn address(task: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[STAKE_SEED, task.as_ref()], &ID)
+    }
+
+    /// Share of a slashed stake of `amount` that goes to the caller of `slash_stake`.
+    fn slash_tip(amount: u64) -> u64 {
+        (amount as u128 * SLASH_TIP_BPS as u128 / 10_000) as u64
+    }
+
+    /// Whether `task` was completed by the deadline. Reopening a task clears `completed_at`,
+    /// so only a completion that still stands counts.
+    fn is_kept(&self, task: &TaskAccount) -> bool {
+        task.completed_at.is_some_and(|completed_at| completed_at <= self.deadline)
+    }
+}
+
+impl PendingAction {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + (ENUM_LENGTH + PUBLIC_KEY_LENGTH)
+                         + PUBLIC_KEY_LENGTH
+                         + U8_LENGTH
+                         + U8_LENGTH
+                         + I64_LENGTH;
+
+    /// A task has at most one pending action at a time.
+    pub fn address(task: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PENDING_ACTION_SEED, task.as_ref()], &ID)
+    }
+}
+
+impl Workspace {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + (STRING_PREFIX_LENGTH + MAX_WORKSPACE_NAME_LENGTH)
+                         + U32_LENGTH;
+
+    pub fn address(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[WORKSPACE_SEED, owner.as_ref()], &ID)
+    }
+}
+
+impl Member {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + ENUM_LENGTH
+                         + BOOL_LENGTH;
+
+    pub fn address(workspace: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MEMBER_SEED, workspace.as_ref(), user.as_ref()], &ID)
+    }
+
+    /// Like `MemberRole::check_can_manage`, for an accepted member only.
+    fn check_can_manage(&self, role: MemberRole) -> Result<()> {
+        if !self.accepted {
+            return err!(ErrorCode::InvitationPending);
+        }
+        self.role.check_can_manage(role)
+    }
+}
+
+impl Config {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH)
+                         + BOOL_LENGTH
+                         + U16_LENGTH
+                         + U64_LENGTH
+                         + PUBLIC_KEY_LENGTH;
+
+    pub fn address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+    }
+
+    fn apply(&mut self, update: ConfigUpdate) -> Result<()> {
+        if let Some(max_name_length) = update.max_name_length {
+            if max_name_length == 0 || max_name_length as usize > MAX_NAME_LENGTH_LIMIT {
+                return err!(ErrorCode::InvalidConfig);
+            }
+            self.max_name_length = max_name_length;
+        }
+        if let Some(paused) = update.paused {
+            self.paused = paused;
+        }
+        if let Some(task_fee) = update.task_fee {
+            self.task_fee = task_fee;
+        }
+        if let Some(stake_beneficiary) = update.stake_beneficiary {
+            self.stake_beneficiary = stake_beneficiary;
+        }
+        Ok(())
+    }
+}
+
+impl Project {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + (STRING_PREFIX_LENGTH + MAX_PROJECT_NAME_LENGTH)
+                         + U64_LENGTH
+                         + U64_LENGTH
+                         + U64_LENGTH
+                         + BOOL_LENGTH
+                         + (OPTION_PREFIX_LENGTH + PUBLIC_KEY_LENGTH)
+                         + U64_LENGTH;
+
+    pub fn address(owner: &Pubkey, name: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROJECT_SEED, owner.as_ref(), name.as_bytes()], &ID)
+    }
+
+    fn record_status_change(&mut self, active: bool) {
+        if active {
+            self.closed_task_count -= 1;
+            self.open_task_count += 1;
+        } else {
+            self.open_task_count -= 1;
+            self.closed_task_count += 1;
+        }
+    }
+
+    fn record_removal(&mut self, active: bool) {
+        if active {
+            self.open_task_count -= 1;
+        } else {
+            self.closed_task_count -= 1;
+        }
+    }
+}
+
+impl Checklist {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + BOOL_LENGTH
+                         + U32_LENGTH
+                         + MAX_CHECKLIST_ITEMS * (STRING_PREFIX_LENGTH + MAX_CHECKLIST_ITEM_LENGTH + BOOL_LENGTH);
+
+    pub fn address(task: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CHECKLIST_SEED, task.as_ref()], &ID)
+    }
+
+    fn item_mut(&mut self, index: u8) -> Result<&mut ChecklistItem> {
+        self.items.get_mut(index as usize).ok_or_else(|| error!(ErrorCode::ChecklistItemNotFound))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.items.is_empty() && self.items.iter().all(|item| item.done)
+    }
+
+    /// An empty checklist counts as 0% done.
+    pub fn completion_percent(&self) -> u8 {
+        if self.items.is_empty() {
+            return 0;
+        }
+        let done = self.items.iter().filter(|item| item.done).count();
+        (done * 100 / self.items.len()) as u8
+    }
+}
+
+impl TagRegistry {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + U32_LENGTH
+                         + MAX_TAGS * (STRING_PREFIX_LENGTH + MAX_TAG_NAME_LENGTH + BOOL_LENGTH);
+
+    pub fn address(namespace: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TAG_REGISTRY_SEED, namespace.as_ref()], &ID)
+    }
+
+    fn tag(&self, index: u8) -> Result<&Tag> {
+        self.tags.get(index as usize).ok_or_else(|| error!(ErrorCode::TagNotFound))
+    }
+
+    fn active_tag(&self, index: u8) -> Result<&Tag> {
+        let tag = self.tag(index)?;
+        if tag.retired {
+            return err!(ErrorCode::TagRetired);
+        }
+        Ok(tag)
+    }
+
+    fn active_tag_mut(&mut self, index: u8) -> Result<&mut Tag> {
+        self.active_tag(index)?;
+        Ok(&mut self.tags[index as usize])
+    }
+
+    /// Names stay unique across retired tags too, so an old name never silently changes meaning.
+    fn check_name_available(&self, name: &str, except: Option<u8>) -> Result<()> {
+        let taken = self
+            .tags
+            .iter()
+            .enumerate()
+            .any(|(i, tag)| tag.name == name && except != Some(i as u8));
+        if taken {
+            return err!(ErrorCode::TagNameTaken);
+        }
+        Ok(())
+    }
+}
+
+impl UserProfile {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+                         + PUBLIC_KEY_LENGTH
+                         + U64_LENGTH
+                         + U64_LENGTH
+                         + U64_LENGTH
+                         + U64_LENGTH;
+
+    pub fn address(authority: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROFILE_SEED, authority.as_ref()], &ID)
+    }
+
+    fn record_status_change(&mut self, active: bool) {
+        if active {
+            self.inactive_task_count = self.inactive_task_count.saturating_sub(1);
+            self.active_task_count += 1;
+        } else {
+            self.active_task_count = self.active_task_count.saturating_sub(1);
+            self.inactive_task_count += 1;
+        }
+    }
+
+    fn record_removal(&mut self, active: bool) {
+        if active {
+            self.active_task_count = self.active_task_count.saturating_sub(1);
+        } else {
+            self.inactive_task_count = self.inactive_task_count.saturating_sub(1);
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateTask<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserProfile::LEN,
+        seeds = [PROFILE_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init, 
+        payer = user, 
+        space = TaskAccount::space(&name), 
+        seeds = [
+            TASK_SEED,
+            project.as_ref().map_or(user.key(), |project| project.key()).as_ref(),
+            project.as_ref().map_or(user_profile.next_task_id, |project| project.next_task_id).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    /// Set to create the task in this project, which then allocates its ID.
+    #[account(mut)]
+    pub project: Option<Account<'info, Project>>,
+    /// Set to create the task as a subtask of this one.
+    #[account(mut, constraint = parent_task.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub parent_task: Option<Account<'info, TaskAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// Receives the task fee.
+    #[account(mut, seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTaskStatus<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(mut, seeds = [PROFILE_SEED, task_account.authority.as_ref()], bump)]
+    pub user_profile: Account<'info, UserProfile>,
+    /// Required when the task is a subtask, to keep the parent's open child count.
+    #[account(mut, constraint = parent_task.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub parent_task: Option<Account<'info, TaskAccount>>,
+    /// Required when the task belongs to a project, to keep its open and closed counts.
+    #[account(mut)]
+    pub project: Option<Account<'info, Project>>,
+    /// Set when `authority` acts as a member of the task authority's workspace.
+    pub member: Option<Account<'info, Member>>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    /// Approved archival, required to archive a task under an approval policy.
+    #[account(mut)]
+    pub pending_action: Option<Account<'info, PendingAction>>,
+    /// CHECK: the proposer of `pending_action`, who paid for it and gets its rent back.
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+    /// The task authority, one of its assignees, a session key, or a workspace member that is
+    /// at least an Editor. Only the task authority or a workspace Admin may archive.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+impl UpdateTaskStatus<'_> {
+    fn authorize(&self) -> Result<()> {
+        if self.task_account.is_assignee(&self.authority.key()) {
+            return Ok(());
+        }
+        if self.session_key.is_some() {
+            return self.task_account.check_signer(&self.authority, &self.session_key, SESSION_UPDATE_STATUS);
+        }
+        match member_role(&self.task_account, &self.authority.key(), &self.member)? {
+            Some(role) => role.check_can_edit(),
+            None => Ok(()),
+        }
+    }
+
+    /// Archiving cannot be undone, so like deleting it is left to the task authority and
+    /// workspace admins.
+    fn authorize_archive(&self) -> Result<()> {
+        match member_role(&self.task_account, &self.authority.key(), &self.member)? {
+            Some(role) => role.check_is_admin(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct ModifyTask<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ReorderTask<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(constraint = prev_task.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub prev_task: Option<Account<'info, TaskAccount>>,
+    #[account(constraint = next_task.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub next_task: Option<Account<'info, TaskAccount>>,
+    #[account(mut, seeds = [PROFILE_SEED, task_account.authority.as_ref()], bump)]
+    pub user_profile: Account<'info, UserProfile>,
+    /// Required when the task belongs to a project, to track the last rank it handed out.
+    #[account(mut)]
+    pub project: Option<Account<'info, Project>>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = SessionKey::LEN,
+        seeds = [SESSION_SEED, authority.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [SESSION_SEED, authority.key().as_ref(), session.session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CreateWorkspace<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Workspace::LEN,
+        seeds = [WORKSPACE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub workspace: Account<'info, Workspace>,
+    #[account(
+        init,
+        payer = owner,
+        space = Member::LEN,
+        seeds = [MEMBER_SEED, workspace.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub owner_member: Account<'info, Member>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct InviteMember<'info> {
+    #[account(mut)]
+    pub workspace: Account<'info, Workspace>,
+    #[account(seeds = [MEMBER_SEED, workspace.key().as_ref(), actor.key().as_ref()], bump)]
+    pub actor_member: Account<'info, Member>,
+    #[account(
+        init,
+        payer = actor,
+        space = Member::LEN,
+        seeds = [MEMBER_SEED, workspace.key().as_ref(), user.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut)]
+    pub actor: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptInvitation<'info> {
+    #[account(
+        mut,
+        has_one = user @ ErrorCode::UnauthorizedAction,
+        seeds = [MEMBER_SEED, member.workspace.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    pub user: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeMemberRole<'info> {
+    pub workspace: Account<'info, Workspace>,
+    #[account(seeds = [MEMBER_SEED, workspace.key().as_ref(), actor.key().as_ref()], bump)]
+    pub actor_member: Account<'info, Member>,
+    #[account(mut, seeds = [MEMBER_SEED, workspace.key().as_ref(), member.user.as_ref()], bump)]
+    pub member: Account<'info, Member>,
+    pub actor: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(mut)]
+    pub workspace: Account<'info, Workspace>,
+    /// Omitted when members remove themselves.
+    #[account(seeds = [MEMBER_SEED, workspace.key().as_ref(), actor.key().as_ref()], bump)]
+    pub actor_member: Option<Account<'info, Member>>,
+    #[account(
+        mut,
+        close = actor,
+        seeds = [MEMBER_SEED, workspace.key().as_ref(), member.user.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut)]
+    pub actor: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateProject<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Project::LEN,
+        seeds = [PROJECT_SEED, owner.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProject<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::UnauthorizedAction,
+        seeds = [PROJECT_SEED, owner.key().as_ref(), project.name.as_bytes()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTag<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TagRegistry::LEN,
+        seeds = [TAG_REGISTRY_SEED, tag_namespace(&project, &authority).as_ref()],
+        bump
+    )]
+    pub tag_registry: Account<'info, TagRegistry>,
+    /// Set to manage the project's registry instead of the authority's own.
+    #[account(constraint = project.owner == authority.key() @ ErrorCode::UnauthorizedAction)]
+    pub project: Option<Account<'info, Project>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageTags<'info> {
+    #[account(mut, seeds = [TAG_REGISTRY_SEED, tag_namespace(&project, &authority).as_ref()], bump)]
+    pub tag_registry: Account<'info, TagRegistry>,
+    /// Set to manage the project's registry instead of the authority's own.
+    #[account(constraint = project.owner == authority.key() @ ErrorCode::UnauthorizedAction)]
+    pub project: Option<Account<'info, Project>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct TagTask<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(seeds = [TAG_REGISTRY_SEED, task_account.namespace().as_ref()], bump)]
+    pub tag_registry: Account<'info, TagRegistry>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AddDependency<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        constraint = depends_on.authority == task_account.authority @ ErrorCode::UnauthorizedAction,
+        constraint = depends_on.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub depends_on: Account<'info, TaskAccount>,
+    #[account(
+        init,
+        payer = task_authority,
+        space = TaskDependency::LEN,
+        seeds = [DEPENDENCY_SEED, task_account.key().as_ref(), depends_on.key().as_ref()],
+        bump
+    )]
+    pub task_dependency: Account<'info, TaskDependency>,
+    /// CHECK: the task authority, which pays for the dependency; it has to co-sign when a session
+    /// key is used.
+    #[account(mut, address = task_account.authority @ ErrorCode::UnauthorizedAction)]
+    pub task_authority: UncheckedAccount<'info>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveDependency<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    /// CHECK: only its address is used, so a dependency on a deleted task can still be removed.
+    pub depends_on: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = task_authority,
+        seeds = [DEPENDENCY_SEED, task_account.key().as_ref(), depends_on.key().as_ref()],
+        bump
+    )]
+    pub task_dependency: Account<'info, TaskDependency>,
+    /// CHECK: the task authority, which receives the dependency's rent.
+    #[account(mut, address = task_account.authority @ ErrorCode::UnauthorizedAction)]
+    pub task_authority: UncheckedAccount<'info>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AddChecklistItem<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        init_if_needed,
+        payer = task_authority,
+        space = Checklist::LEN,
+        seeds = [CHECKLIST_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub task_checklist: Account<'info, Checklist>,
+    /// CHECK: the task authority, which pays for the checklist; it has to co-sign when a session
+    /// key is used.
+    #[account(mut, address = task_account.authority @ ErrorCode::UnauthorizedAction)]
+    pub task_authority: UncheckedAccount<'info>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageChecklist<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(mut, seeds = [CHECKLIST_SEED, task_account.key().as_ref()], bump)]
+    pub task_checklist: Account<'info, Checklist>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ToggleChecklistItem<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(mut, seeds = [CHECKLIST_SEED, task_account.key().as_ref()], bump)]
+    pub task_checklist: Account<'info, Checklist>,
+    #[account(mut, seeds = [PROFILE_SEED, task_account.authority.as_ref()], bump)]
+    pub user_profile: Account<'info, UserProfile>,
+    /// Required when the task is a subtask and the toggle may auto-complete it.
+    #[account(mut, constraint = parent_task.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub parent_task: Option<Account<'info, TaskAccount>>,
+    /// Required when the task belongs to a project and the toggle may auto-complete it.
+    #[account(mut)]
+    pub project: Option<Account<'info, Project>>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ViewChecklist<'info> {
+    pub task_checklist: Account<'info, Checklist>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AssignTask<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    /// Set when `authority` acts as a member of the task authority's workspace.
+    pub member: Option<Account<'info, Member>>,
+    /// The task authority or a workspace member that is at least an Admin.
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+impl AssignTask<'_> {
+    fn authorize(&self) -> Result<()> {
+        match member_role(&self.task_account, &self.authority.key(), &self.member)? {
+            Some(role) => role.check_is_admin(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct ProposeTransfer<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    /// Approved transfer, required to transfer a task under an approval policy.
+    #[account(mut)]
+    pub pending_action: Option<Account<'info, PendingAction>>,
+    /// CHECK: the proposer of `pending_action`, who paid for it and gets its rent back.
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptTransfer<'info> {
+    #[account(
+        mut,
+        constraint = task_account.pending_owner == Some(new_owner.key()) @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(mut, seeds = [PROFILE_SEED, task_account.authority.as_ref()], bump)]
+    pub previous_owner_profile: Account<'info, UserProfile>,
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = UserProfile::LEN,
+        seeds = [PROFILE_SEED, new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_owner_profile: Account<'info, UserProfile>,
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
 
-declare_id!("EJfiMorcTnMgyHvxpBe8EaBc7YG5p79xy4vLe2fPqV3B");
+#[derive(Accounts)]
+pub struct CancelTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    /// The task authority or the pending owner.
+    pub signer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
 
-#[program]
-pub mod task_manager {
-    use super::*;
+#[derive(Accounts)]
+pub struct CreateTaskApprovalPolicy<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = ApprovalPolicy::LEN,
+        seeds = [APPROVAL_POLICY_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub approval_policy: Account<'info, ApprovalPolicy>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
 
-    pub fn create_task(ctx: Context<CreateTask>, id: u64, name: String) -> Result<()> {
-        if name.chars().count() > MAX_NAME_LENGTH {
-            return err!(ErrorCode::NameTooLong);
-        }
-        let task = &mut ctx.accounts.task_account;
-        task.id = id;
-        task.name = name;
-        task.authority = *ctx.accounts.user.key;
-        task.active = true;
-        msg!("Task '{}' created with ID: {}", task.name, task.id);
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct CreateProjectApprovalPolicy<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ErrorCode::UnauthorizedAction,
+        seeds = [PROJECT_SEED, owner.key().as_ref(), project.name.as_bytes()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init,
+        payer = owner,
+        space = ApprovalPolicy::LEN,
+        seeds = [APPROVAL_POLICY_SEED, project.key().as_ref()],
+        bump
+    )]
+    pub approval_policy: Account<'info, ApprovalPolicy>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
 
-    pub fn update_task_status(ctx: Context<UpdateTaskStatus>, new_status: bool) -> Result<()> {
-        ctx.accounts.task_account.active = new_status;
-        msg!("Task ID {} status updated to: {}", ctx.accounts.task_account.id, new_status);
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct ProposeAction<'info> {
+    #[account(
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(constraint = task_account.approval_policy == Some(approval_policy.key()) @ ErrorCode::NoApprovalPolicy)]
+    pub approval_policy: Account<'info, ApprovalPolicy>,
+    #[account(
+        init,
+        payer = proposer,
+        space = PendingAction::LEN,
+        seeds = [PENDING_ACTION_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    /// The task authority or one of the approvers.
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
 
-    pub fn delete_task(ctx: Context<DeleteTask>) -> Result<()> {
-        msg!("Task ID {} deleted by {}", ctx.accounts.task_account.id, ctx.accounts.authority_signer.key());
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct ApproveAction<'info> {
+    #[account(mut, seeds = [PENDING_ACTION_SEED, pending_action.task.as_ref()], bump)]
+    pub pending_action: Account<'info, PendingAction>,
+    #[account(address = pending_action.policy @ ErrorCode::PendingActionMismatch)]
+    pub approval_policy: Account<'info, ApprovalPolicy>,
+    pub approver: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
 }
 
-#[account]
-pub struct TaskAccount {
-    pub id: u64,
-    pub name: String,
-    pub authority: Pubkey,
-    pub active: bool,
+#[derive(Accounts)]
+pub struct CancelAction<'info> {
+    #[account(constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_ACTION_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub pending_action: Account<'info, PendingAction>,
+    /// CHECK: the proposer, who paid for the pending action and gets its rent back.
+    #[account(mut, address = pending_action.proposer @ ErrorCode::UnauthorizedAction)]
+    pub proposer: UncheckedAccount<'info>,
+    pub signer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
 }
 
-const MAX_NAME_LENGTH: usize = 50;
-const DISCRIMINATOR_LENGTH: usize = 8;
-const U64_LENGTH: usize = 8;
-const STRING_PREFIX_LENGTH: usize = 4;
-const PUBLIC_KEY_LENGTH: usize = 32;
-const BOOL_LENGTH: usize = 1;
+#[derive(Accounts)]
+pub struct FundBounty<'info> {
+    #[account(
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = BountyVault::LEN,
+        seeds = [BOUNTY_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub bounty_vault: Account<'info, BountyVault>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
 
-impl TaskAccount {
-    pub const LEN: usize = DISCRIMINATOR_LENGTH 
-                         + U64_LENGTH 
-                         + (STRING_PREFIX_LENGTH + MAX_NAME_LENGTH)
-                         + PUBLIC_KEY_LENGTH
-                         + BOOL_LENGTH;
+#[derive(Accounts)]
+pub struct CompleteAndPay<'info> {
+    #[account(
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        mut,
+        close = funder,
+        has_one = funder @ ErrorCode::BountyFunderMismatch,
+        seeds = [BOUNTY_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub bounty_vault: Account<'info, BountyVault>,
+    /// CHECK: the task's assignee, who receives the bounty.
+    #[account(mut, constraint = task_account.assignee == Some(assignee.key()) @ ErrorCode::NotAssigned)]
+    pub assignee: UncheckedAccount<'info>,
+    /// CHECK: receives the vault's rent back, checked against `bounty_vault.funder`.
+    #[account(mut)]
+    pub funder: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
 }
 
 #[derive(Accounts)]
-#[instruction(id: u64, name: String)]
-pub struct CreateTask<'info> {
+pub struct RefundBounty<'info> {
     #[account(
-        init, 
-        payer = user, 
-        space = TaskAccount::LEN, 
-        seeds = [b"task_seed".as_ref(), id.to_le_bytes().as_ref()],
+        mut,
+        close = funder,
+        has_one = funder @ ErrorCode::BountyFunderMismatch,
+        seeds = [BOUNTY_SEED, bounty_vault.task.as_ref()],
         bump
     )]
+    pub bounty_vault: Account<'info, BountyVault>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RejectBounty<'info> {
+    #[account(
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
     pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        mut,
+        close = funder,
+        has_one = funder @ ErrorCode::BountyFunderMismatch,
+        seeds = [BOUNTY_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub bounty_vault: Account<'info, BountyVault>,
+    /// CHECK: receives the bounty and the vault's rent, checked against `bounty_vault.funder`.
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub funder: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct FundTokenBounty<'info> {
+    #[account(
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = TokenBounty::LEN,
+        seeds = [TOKEN_BOUNTY_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub token_bounty: Account<'info, TokenBounty>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        associated_token::mint = mint,
+        associated_token::authority = token_bounty,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = funder,
+        token::token_program = token_program
+    )]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateTaskStatus<'info> {
-    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedAction)]
+pub struct CompleteAndPayToken<'info> {
+    #[account(
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        mut,
+        close = funder,
+        has_one = funder @ ErrorCode::BountyFunderMismatch,
+        has_one = mint @ ErrorCode::BountyMintMismatch,
+        seeds = [TOKEN_BOUNTY_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub token_bounty: Account<'info, TokenBounty>,
+    /// Writable so that fees withheld in the vault can be harvested to it.
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = token_bounty,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// Any token account of `mint` held by the task's assignee.
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = task_account.assignee == Some(assignee_token_account.owner) @ ErrorCode::NotAssigned
+    )]
+    pub assignee_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: receives the rent back, checked against `token_bounty.funder`.
+    #[account(mut)]
+    pub funder: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RefundTokenBounty<'info> {
+    #[account(
+        mut,
+        close = funder,
+        has_one = funder @ ErrorCode::BountyFunderMismatch,
+        has_one = mint @ ErrorCode::BountyMintMismatch,
+        seeds = [TOKEN_BOUNTY_SEED, token_bounty.task.as_ref()],
+        bump
+    )]
+    pub token_bounty: Account<'info, TokenBounty>,
+    /// Writable so that fees withheld in the vault can be harvested to it.
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = token_bounty,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = funder,
+        token::token_program = token_program
+    )]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CommitStake<'info> {
+    #[account(
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = Stake::LEN,
+        seeds = [STAKE_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStake<'info> {
+    #[account(
+        address = stake.task @ ErrorCode::TaskIdMismatch,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        mut,
+        close = staker,
+        has_one = staker @ ErrorCode::UnauthorizedAction,
+        seeds = [STAKE_SEED, stake.task.as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, Stake>,
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SlashStake<'info> {
+    /// CHECK: the staked task, decoded with `TaskAccount::load` unless it has been deleted.
+    #[account(address = stake.task @ ErrorCode::TaskIdMismatch)]
+    pub task_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = staker,
+        has_one = staker @ ErrorCode::UnauthorizedAction,
+        has_one = beneficiary @ ErrorCode::InvalidBeneficiary,
+        seeds = [STAKE_SEED, stake.task.as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, Stake>,
+    /// CHECK: receives the stake's rent back, checked against `stake.staker`.
+    #[account(mut)]
+    pub staker: UncheckedAccount<'info>,
+    /// CHECK: receives the slashed stake, checked against `stake.beneficiary`.
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(init, payer = admin, space = Config::LEN, seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::TaskManager>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::UnauthorizedAction)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Config changes are allowed while paused, so that the admin can unpause.
+#[derive(Accounts)]
+pub struct ManageConfig<'info> {
+    #[account(mut, has_one = admin @ ErrorCode::UnauthorizedAction, seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump)]
+    pub config: Account<'info, Config>,
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ViewTask<'info> {
+    #[account(constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(update: TaskUpdate)]
+pub struct UpdateTask<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        realloc = TaskAccount::space(update.name.as_deref().unwrap_or(&task_account.name)),
+        realloc::payer = task_authority,
+        realloc::zero = false,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
     pub task_account: Account<'info, TaskAccount>,
+    /// CHECK: the task authority, which pays for a longer name and gets the rent back for a
+    /// shorter one; it has to co-sign when a session key makes the name longer.
+    #[account(mut, address = task_account.authority @ ErrorCode::UnauthorizedAction)]
+    pub task_authority: UncheckedAccount<'info>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
     pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct DeleteTask<'info> {
     #[account(
         mut, 
-        close = authority_signer,
-        constraint = task_account.authority == authority_signer.key() @ ErrorCode::UnauthorizedAction
+        close = authority,
+        has_one = authority @ ErrorCode::UnauthorizedAction,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
     )]
     pub task_account: Account<'info, TaskAccount>,
+    #[account(mut, seeds = [PROFILE_SEED, authority.key().as_ref()], bump)]
+    pub user_profile: Account<'info, UserProfile>,
+    /// CHECK: closed alongside the task when it has been created, otherwise left untouched.
+    #[account(mut, seeds = [DESCRIPTION_SEED, task_account.key().as_ref()], bump)]
+    pub task_description: UncheckedAccount<'info>,
+    /// CHECK: closed alongside the task when it has been created, otherwise left untouched.
+    #[account(mut, seeds = [CHECKLIST_SEED, task_account.key().as_ref()], bump)]
+    pub task_checklist: UncheckedAccount<'info>,
+    /// CHECK: must not hold a bounty, so that an escrowed bounty is never swept along with the task.
+    #[account(seeds = [BOUNTY_SEED, task_account.key().as_ref()], bump)]
+    pub bounty_vault: UncheckedAccount<'info>,
+    /// Required when the task is a subtask, to keep the parent's child counts.
+    #[account(mut, constraint = parent_task.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated)]
+    pub parent_task: Option<Account<'info, TaskAccount>>,
+    /// Required when the task belongs to a project, to keep its task counts.
+    #[account(mut)]
+    pub project: Option<Account<'info, Project>>,
+    /// Set when `authority_signer` acts as a member of the task authority's workspace.
+    pub member: Option<Account<'info, Member>>,
+    /// Approved deletion, required to delete a task under an approval policy.
+    #[account(mut)]
+    pub pending_action: Option<Account<'info, PendingAction>>,
+    /// CHECK: the proposer of `pending_action`, who paid for it and gets its rent back.
+    #[account(mut)]
+    pub proposer: Option<UncheckedAccount<'info>>,
+    /// CHECK: the task authority, which receives the rent of everything closed.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+    /// The task authority, or a workspace member that is at least an Admin.
     #[account(mut)] 
     pub authority_signer: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+impl DeleteTask<'_> {
+    fn authorize(&self) -> Result<()> {
+        match member_role(&self.task_account, &self.authority_signer.key(), &self.member)? {
+            Some(role) => role.check_is_admin(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(max_length: u32)]
+pub struct InitTaskDescription<'info> {
+    #[account(
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        init,
+        payer = task_authority,
+        space = TaskDescription::space(max_length),
+        seeds = [DESCRIPTION_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub task_description: Account<'info, TaskDescription>,
+    /// CHECK: the task authority, which pays for the description; it has to co-sign when a session
+    /// key is used.
+    #[account(mut, address = task_account.authority @ ErrorCode::UnauthorizedAction)]
+    pub task_authority: UncheckedAccount<'info>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteTaskDescription<'info> {
+    #[account(
+        mut,
+        seeds = [TASK_SEED, task_account.namespace().as_ref(), task_account.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = task_account.version == TaskAccount::VERSION @ ErrorCode::TaskNotMigrated
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(
+        mut,
+        seeds = [DESCRIPTION_SEED, task_account.key().as_ref()],
+        bump
+    )]
+    pub task_description: Account<'info, TaskDescription>,
+    /// Set when `authority` is a session key delegated by the task authority.
+    pub session_key: Option<Account<'info, SessionKey>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTask<'info> {
+    /// CHECK: decoded with `TaskAccount::load` in the handler, which also checks the owner and
+    /// the address, since the seeds depend on the decoded task's namespace.
+    #[account(mut)]
+    pub task_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct MigrateTaskSeeds<'info> {
+    /// CHECK: decoded with `TaskAccount::load` in the handler; address and owner are checked here.
+    #[account(
+        mut,
+        owner = ID,
+        seeds = [LEGACY_TASK_SEED, id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub legacy_task_account: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = UserProfile::LEN,
+        seeds = [PROFILE_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    /// CHECK: the namespaced address at the legacy ID, only checked for being in use.
+    #[account(seeds = [TASK_SEED, authority.key().as_ref(), id.to_le_bytes().as_ref()], bump)]
+    pub legacy_id_slot: UncheckedAccount<'info>,
+    /// At the legacy ID if the profile counter already passed it and it is free, otherwise at
+    /// the profile's next ID.
+    #[account(
+        init,
+        payer = authority,
+        space = TaskAccount::LEN,
+        seeds = [
+            TASK_SEED,
+            authority.key().as_ref(),
+            migrated_task_id(id, &legacy_id_slot, &user_profile).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub task_account: Account<'info, TaskAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump, constraint = !config.paused @ ErrorCode::ProgramPaused)]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Name is too long.")]
     NameTooLong,
+    #[msg("Name cannot be empty.")]
+    NameEmpty,
+    #[msg("Name cannot contain control characters.")]
+    NameContainsControlCharacters,
     #[msg("Unauthorized action.")]
     UnauthorizedAction,
+    #[msg("Task cannot move to the requested status from its current one.")]
+    InvalidStatusTransition,
+    #[msg("Archived tasks cannot change status.")]
+    TaskArchived,
+    #[msg("Task ID does not match the account.")]
+    TaskIdMismatch,
+    #[msg("Description exceeds its reserved length.")]
+    DescriptionTooLong,
+    #[msg("Description has been finalized and can no longer be written.")]
+    DescriptionFinalized,
+    #[msg("Description chunk does not start at the end of the written content.")]
+    DescriptionOffsetMismatch,
+    #[msg("Description is not valid UTF-8.")]
+    DescriptionNotUtf8,
+    #[msg("Due date must be in the future.")]
+    DueDateInPast,
+    #[msg("Start date must not be after the due date.")]
+    StartAfterDue,
+    #[msg("Task was modified since the expected revision.")]
+    StaleRevision,
+    #[msg("Task account uses an unknown layout version.")]
+    UnsupportedLayoutVersion,
+    #[msg("Task account already uses the current layout.")]
+    TaskAlreadyMigrated,
+    #[msg("Neighbour tasks must be other tasks of the same authority, in order.")]
+    InvalidNeighbours,
+    #[msg("No rank left between the neighbour tasks.")]
+    RankGapExhausted,
+    #[msg("Tag registry is full.")]
+    TagRegistryFull,
+    #[msg("A tag with this name already exists.")]
+    TagNameTaken,
+    #[msg("Tag does not exist.")]
+    TagNotFound,
+    #[msg("Tag has been retired.")]
+    TagRetired,
+    #[msg("Parent task account does not match the task's parent.")]
+    ParentTaskMismatch,
+    #[msg("Parent task is closed.")]
+    ParentTaskClosed,
+    #[msg("Task still has open subtasks.")]
+    OpenSubtasks,
+    #[msg("Task still has subtasks.")]
+    HasSubtasks,
+    #[msg("Subtask accounts must be passed as (subtask, description, checklist, bounty vault) groups.")]
+    SubtaskAccountsMismatch,
+    #[msg("Task cannot start while a task it depends on is still open.")]
+    DependencyOpen,
+    #[msg("Dependency would create a cycle.")]
+    DependencyCycle,
+    #[msg("Dependency chain is deeper than can be checked.")]
+    DependencyChainTooDeep,
+    #[msg("Dependency accounts do not cover every dependency of the tasks involved.")]
+    DependencyAccountsMismatch,
+    #[msg("Task still has dependencies.")]
+    HasDependencies,
+    #[msg("Checklist is full.")]
+    ChecklistFull,
+    #[msg("Checklist item does not exist.")]
+    ChecklistItemNotFound,
+    #[msg("Project account does not match the task's project.")]
+    ProjectMismatch,
+    #[msg("Project is closed.")]
+    ProjectClosed,
+    #[msg("Project still has open tasks.")]
+    ProjectHasOpenTasks,
+    #[msg("Viewers cannot modify tasks or manage members.")]
+    ViewerUnauthorized,
+    #[msg("Editors cannot delete tasks or manage members.")]
+    EditorUnauthorized,
+    #[msg("Admins cannot manage owners or other admins.")]
+    AdminUnauthorized,
+    #[msg("Membership has not been accepted yet.")]
+    InvitationPending,
+    #[msg("The workspace owner's membership cannot be changed or removed.")]
+    WorkspaceOwnerImmutable,
+    #[msg("User is already assigned to this task.")]
+    AlreadyAssigned,
+    #[msg("Task has no room for more assignees.")]
+    AssigneesFull,
+    #[msg("User is not assigned to this task.")]
+    NotAssigned,
+    #[msg("Tasks with a parent or subtasks cannot be transferred.")]
+    SubtaskTransfer,
+    #[msg("Transfer offer has expired.")]
+    TransferExpired,
+    #[msg("Task has no pending transfer.")]
+    NoPendingTransfer,
+    #[msg("Session key has expired.")]
+    SessionExpired,
+    #[msg("Session key does not grant this permission.")]
+    SessionPermissionDenied,
+    #[msg("Session permissions must be a non-empty combination of the SESSION_* flags.")]
+    InvalidSessionPermissions,
+    #[msg("Approvers must be unique, at most 8, and the threshold between 1 and their number.")]
+    InvalidApprovalPolicy,
+    #[msg("An approval policy is already set.")]
+    ApprovalPolicyAlreadySet,
+    #[msg("Task has no approval policy.")]
+    NoApprovalPolicy,
+    #[msg("Action needs an approved pending action.")]
+    ApprovalRequired,
+    #[msg("Pending action is for a different task or action.")]
+    PendingActionMismatch,
+    #[msg("Pending action has expired.")]
+    ActionExpired,
+    #[msg("Signer is not an approver.")]
+    NotAnApprover,
+    #[msg("Approver has already approved this action.")]
+    AlreadyApproved,
+    #[msg("Bounty amount must be positive.")]
+    InvalidBountyAmount,
+    #[msg("Bounty was funded by a different account.")]
+    BountyFunderMismatch,
+    #[msg("Bounty has expired.")]
+    BountyExpired,
+    #[msg("Bounty has not expired yet.")]
+    BountyNotExpired,
+    #[msg("Task still holds a funded bounty.")]
+    BountyFunded,
+    #[msg("Task is not done.")]
+    TaskNotDone,
+    #[msg("Bounty is in a different mint.")]
+    BountyMintMismatch,
+    #[msg("Stake amount must cover the beneficiary's rent exemption after the slash tip.")]
+    InvalidStakeAmount,
+    #[msg("Task has no due date.")]
+    NoDueDate,
+    #[msg("Beneficiary must differ from the staker.")]
+    InvalidBeneficiary,
+    #[msg("Task was not completed by the stake's deadline.")]
+    StakeNotKept,
+    #[msg("Stake deadline has not passed yet.")]
+    StakeNotDue,
+    #[msg("Task was completed by the deadline, so the stake cannot be slashed.")]
+    StakeKept,
+    #[msg("Program is paused.")]
+    ProgramPaused,
+    #[msg("Max name length must be between 1 and 128 bytes.")]
+    InvalidConfig,
+    #[msg("Task account uses an older layout; migrate it with migrate_task first.")]
+    TaskNotMigrated,
+    #[msg("Task ID counter overflowed.")]
+    TaskIdOverflow,
+    #[msg("Bounty expiry is too far in the future.")]
+    BountyExpiryTooFar,
 }
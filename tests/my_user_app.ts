@@ -3,6 +3,22 @@ import { Program } from "@coral-xyz/anchor";
 import { TaskManager } from "../target/types/task_manager";
 import { expect } from "chai";
 import BN from "bn.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getMintLen,
+  getTransferFeeConfig,
+  mintTo,
+} from "@solana/spl-token";
 
 describe("task_manager", () => {
   const provider = anchor.AnchorProvider.env();
@@ -11,24 +27,158 @@ describe("task_manager", () => {
   const program = anchor.workspace.taskManager as Program<TaskManager>;
   const user = provider.wallet as anchor.Wallet;
 
-  const taskId = new BN(1);
+  let taskId: BN;
   let taskPda: anchor.web3.PublicKey;
   let bump: number;
 
-  before(async () => {
-    [taskPda, bump] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("task_seed"), taskId.toArrayLike(Buffer, "le", 8)],
+  const findTaskPda = (authority: anchor.web3.PublicKey, id: BN) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("task"), authority.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+  const findLegacyTaskPda = (id: BN) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("task_seed"), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+  const findProfilePda = (authority: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), authority.toBuffer()],
+      program.programId
+    );
+
+  const findDescriptionPda = (task: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("description"), task.toBuffer()],
+      program.programId
+    );
+
+  const findProjectPda = (owner: anchor.web3.PublicKey, name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("project"), owner.toBuffer(), Buffer.from(name)],
+      program.programId
+    );
+
+  const findTagRegistryPda = (namespace: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("tags"), namespace.toBuffer()], program.programId);
+
+  const findSessionPda = (authority: anchor.web3.PublicKey, sessionKey: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("session"), authority.toBuffer(), sessionKey.toBuffer()],
+      program.programId
+    );
+
+  const findApprovalPolicyPda = (scope: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("approval_policy"), scope.toBuffer()], program.programId);
+
+  const findPendingActionPda = (task: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("pending_action"), task.toBuffer()], program.programId);
+
+  const findBountyPda = (task: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("bounty"), task.toBuffer()], program.programId);
+
+  const findTokenBountyPda = (task: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("token_bounty"), task.toBuffer()], program.programId);
+
+  const findStakePda = (task: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("stake"), task.toBuffer()], program.programId);
+
+  const findWorkspacePda = (owner: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("workspace"), owner.toBuffer()],
+      program.programId
+    );
+
+  const findMemberPda = (workspace: anchor.web3.PublicKey, member: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("member"), workspace.toBuffer(), member.toBuffer()],
+      program.programId
+    );
+
+  const findChecklistPda = (task: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("checklist"), task.toBuffer()],
+      program.programId
+    );
+
+  const findDependencyPda = (task: anchor.web3.PublicKey, dependsOn: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("dependency"), task.toBuffer(), dependsOn.toBuffer()],
       program.programId
     );
+
+  // IDs are allocated on-chain, so the next task's address depends on the profile counter.
+  const nextTaskPda = async (
+    authority: anchor.web3.PublicKey
+  ): Promise<[BN, anchor.web3.PublicKey]> => {
+    const [profilePda] = findProfilePda(authority);
+    const profile = await program.account.userProfile.fetchNullable(profilePda);
+    const id = profile ? profile.nextTaskId : new BN(0);
+    return [id, findTaskPda(authority, id)[0]];
+  };
+
+  // Creates a task for `user`, in `project` when set, and returns its address.
+  const createTask = async (
+    name: string,
+    opts: {
+      startAt?: BN | null;
+      dueAt?: BN | null;
+      parentTask?: anchor.web3.PublicKey | null;
+      project?: anchor.web3.PublicKey | null;
+    } = {}
+  ) => {
+    const pda = opts.project
+      ? findTaskPda(opts.project, (await program.account.project.fetch(opts.project)).nextTaskId)[0]
+      : (await nextTaskPda(user.publicKey))[1];
+    await program.methods
+      .createTask(name, opts.startAt ?? null, opts.dueAt ?? null)
+      .accounts({
+        taskAccount: pda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        parentTask: opts.parentTask ?? null,
+        project: opts.project ?? null,
+        user: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+    return pda;
+  };
+
+  const [configPda] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+
+  before(async () => {
+    // Every instruction reads the config, so it has to exist before anything else runs.
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    await program.methods
+      .initializeConfig(50, new BN(0))
+      .accounts({
+        config: configPda,
+        program: program.programId,
+        programData,
+        admin: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    [taskId, taskPda] = await nextTaskPda(user.publicKey);
+    [, bump] = findTaskPda(user.publicKey, taskId);
   });
 
   it("Creates a task", async () => {
     const taskName = "Test Task";
 
     await program.methods
-      .createTask(taskId, taskName)
+      .createTask(taskName, null, null)
       .accounts({
         taskAccount: taskPda,
+        userProfile: findProfilePda(user.publicKey)[0],
         user: user.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -39,7 +189,7 @@ describe("task_manager", () => {
     expect(accountData.id.eq(taskId)).to.be.true;
     expect(accountData.name).to.equal(taskName);
     expect(accountData.authority.equals(user.publicKey)).to.be.true;
-    expect(accountData.active).to.be.true;
+    expect(accountData.status).to.deep.equal({ todo: {} });
     console.log("Task created:", accountData);
   });
 
@@ -51,10 +201,12 @@ describe("task_manager", () => {
         "Task not found from previous test, creating one for update test..."
       );
       const taskName = "Update Test Task";
+      [taskId, taskPda] = await nextTaskPda(user.publicKey);
       await program.methods
-        .createTask(taskId, taskName)
+        .createTask(taskName, null, null)
         .accounts({
           taskAccount: taskPda,
+          userProfile: findProfilePda(user.publicKey)[0],
           user: user.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -65,16 +217,17 @@ describe("task_manager", () => {
     
     const newStatus = false;
     await program.methods
-      .updateTaskStatus(newStatus)
+      .updateTaskStatus(newStatus, null)
       .accounts({
         taskAccount: taskPda,
+        userProfile: findProfilePda(user.publicKey)[0],
         authority: user.publicKey,
       })
       .signers([user.payer])
       .rpc();
 
     const accountData = await program.account.taskAccount.fetch(taskPda);
-    expect(accountData.active).to.equal(newStatus);
+    expect(accountData.status).to.deep.equal({ done: {} });
     console.log("Task status updated to inactive:", accountData);
   });
 
@@ -83,12 +236,13 @@ describe("task_manager", () => {
     let accountData;
     try {
       accountData = await program.account.taskAccount.fetch(taskPda);
-      if (accountData.active) {
+      if (!("done" in accountData.status)) {
         // If it's active, make it inactive first
         await program.methods
-          .updateTaskStatus(false)
+          .updateTaskStatus(false, null)
           .accounts({
             taskAccount: taskPda,
+            userProfile: findProfilePda(user.publicKey)[0],
             authority: user.publicKey,
           })
           .signers([user.payer])
@@ -98,10 +252,12 @@ describe("task_manager", () => {
     } catch (e) {
       console.log("Task not found, creating a new inactive task...");
       const taskName = "Inactive Test Task";
+      [taskId, taskPda] = await nextTaskPda(user.publicKey);
       await program.methods
-        .createTask(taskId, taskName)
+        .createTask(taskName, null, null)
         .accounts({
           taskAccount: taskPda,
+          userProfile: findProfilePda(user.publicKey)[0],
           user: user.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -110,9 +266,10 @@ describe("task_manager", () => {
       
       // Set it to inactive
       await program.methods
-        .updateTaskStatus(false)
+        .updateTaskStatus(false, null)
         .accounts({
           taskAccount: taskPda,
+          userProfile: findProfilePda(user.publicKey)[0],
           authority: user.publicKey,
         })
         .signers([user.payer])
@@ -122,14 +279,15 @@ describe("task_manager", () => {
     
     // Verify task is inactive before update
     accountData = await program.account.taskAccount.fetch(taskPda);
-    expect(accountData.active).to.be.false;
+    expect(accountData.status).to.deep.equal({ done: {} });
     
     // Now update status to active
     const newStatus = true;
     await program.methods
-      .updateTaskStatus(newStatus)
+      .updateTaskStatus(newStatus, null)
       .accounts({
         taskAccount: taskPda,
+        userProfile: findProfilePda(user.publicKey)[0],
         authority: user.publicKey,
       })
       .signers([user.payer])
@@ -137,7 +295,7 @@ describe("task_manager", () => {
 
     // Verify task is now active
     accountData = await program.account.taskAccount.fetch(taskPda);
-    expect(accountData.active).to.equal(newStatus);
+    expect(accountData.status).to.deep.equal({ todo: {} });
     console.log("Task status updated to active:", accountData);
   });
 
@@ -152,9 +310,10 @@ describe("task_manager", () => {
 
     try {
       await program.methods
-        .updateTaskStatus(true)
+        .updateTaskStatus(true, null)
         .accounts({
           taskAccount: taskPda,
+          userProfile: findProfilePda(anotherUser.publicKey)[0],
           authority: anotherUser.publicKey,
         })
         .signers([anotherUser])
@@ -175,10 +334,12 @@ describe("task_manager", () => {
         "Task not found from previous test, creating one for delete test..."
       );
       const taskName = "Delete Test Task";
+      [taskId, taskPda] = await nextTaskPda(user.publicKey);
       await program.methods
-        .createTask(taskId, taskName)
+        .createTask(taskName, null, null)
         .accounts({
           taskAccount: taskPda,
+          userProfile: findProfilePda(user.publicKey)[0],
           user: user.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -198,6 +359,11 @@ describe("task_manager", () => {
       .deleteTask()
       .accounts({
         taskAccount: taskPda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        taskDescription: findDescriptionPda(taskPda)[0],
+        taskChecklist: findChecklistPda(taskPda)[0],
+        bountyVault: findBountyPda(taskPda)[0],
+        authority: user.publicKey,
         authoritySigner: user.publicKey,
       })
       .signers([user.payer])
@@ -227,18 +393,15 @@ describe("task_manager", () => {
   });
 
   it("Fails to delete task with wrong authority", async () => {
-    const testDeleteId = new BN(2);
     let testDeletePda: anchor.web3.PublicKey;
-    [testDeletePda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("task_seed"), testDeleteId.toArrayLike(Buffer, "le", 8)],
-      program.programId
-    );
+    [, testDeletePda] = await nextTaskPda(user.publicKey);
 
     console.log("Creating task for wrong authority delete test...");
     await program.methods
-      .createTask(testDeleteId, "Temp Task for Delete Fail Test")
+      .createTask("Temp Task for Delete Fail Test", null, null)
       .accounts({
         taskAccount: testDeletePda,
+        userProfile: findProfilePda(user.publicKey)[0],
         user: user.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -259,6 +422,11 @@ describe("task_manager", () => {
         .deleteTask()
         .accounts({
           taskAccount: testDeletePda,
+          userProfile: findProfilePda(anotherUser.publicKey)[0],
+          taskDescription: findDescriptionPda(testDeletePda)[0],
+          taskChecklist: findChecklistPda(testDeletePda)[0],
+          bountyVault: findBountyPda(testDeletePda)[0],
+          authority: user.publicKey,
           authoritySigner: anotherUser.publicKey,
         })
         .signers([anotherUser])
@@ -280,6 +448,11 @@ describe("task_manager", () => {
           .deleteTask()
           .accounts({
             taskAccount: testDeletePda,
+            userProfile: findProfilePda(user.publicKey)[0],
+            taskDescription: findDescriptionPda(testDeletePda)[0],
+            taskChecklist: findChecklistPda(testDeletePda)[0],
+            bountyVault: findBountyPda(testDeletePda)[0],
+            authority: user.publicKey,
             authoritySigner: user.publicKey,
           })
           .signers([user.payer])
@@ -294,22 +467,16 @@ describe("task_manager", () => {
   });
 
   it("Fails to create a task with too long name", async () => {
-    const tooLongTaskId = new BN(999);
-    const [tooLongTaskPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("task_seed"),
-        tooLongTaskId.toArrayLike(Buffer, "le", 8),
-      ],
-      program.programId
-    );
+    const [, tooLongTaskPda] = await nextTaskPda(user.publicKey);
 
     const tooLongName = "A".repeat(100);
 
     try {
       await program.methods
-        .createTask(tooLongTaskId, tooLongName)
+        .createTask(tooLongName, null, null)
         .accounts({
           taskAccount: tooLongTaskPda,
+          userProfile: findProfilePda(user.publicKey)[0],
           user: user.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -326,16 +493,14 @@ describe("task_manager", () => {
 
   it("Fails to update a non-existent task", async () => {
     const nonExistentId = new BN(12345);
-    const [nonExistentPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("task_seed"), nonExistentId.toArrayLike(Buffer, "le", 8)],
-      program.programId
-    );
+    const [nonExistentPda] = findTaskPda(user.publicKey, nonExistentId);
 
     try {
       await program.methods
-        .updateTaskStatus(true)
+        .updateTaskStatus(true, null)
         .accounts({
           taskAccount: nonExistentPda,
+          userProfile: findProfilePda(user.publicKey)[0],
           authority: user.publicKey,
         })
         .signers([user.payer])
@@ -348,4 +513,3251 @@ describe("task_manager", () => {
       console.log("Successfully failed to update non-existent task.");
     }
   });
+
+  it("Allocates task IDs per authority", async () => {
+    const otherUser = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(
+      otherUser.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL / 10
+    );
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    // Both authorities get their own ID space, each starting from zero.
+    const [freshId, freshPda] = await nextTaskPda(otherUser.publicKey);
+    expect(freshId.eqn(0)).to.be.true;
+
+    const [userNextId, userNextPda] = await nextTaskPda(user.publicKey);
+    await program.methods
+      .createTask("Mine", null, null)
+      .accounts({
+        taskAccount: userNextPda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        user: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+    await program.methods
+      .createTask("Theirs", null, null)
+      .accounts({
+        taskAccount: freshPda,
+        userProfile: findProfilePda(otherUser.publicKey)[0],
+        user: otherUser.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([otherUser])
+      .rpc();
+
+    expect((await program.account.taskAccount.fetch(userNextPda)).id.eq(userNextId)).to.be.true;
+    expect((await program.account.taskAccount.fetch(freshPda)).id.eqn(0)).to.be.true;
+    const [, otherNextPda] = await nextTaskPda(otherUser.publicKey);
+    expect(otherNextPda.equals(findTaskPda(otherUser.publicKey, new BN(1))[0])).to.be.true;
+  });
+
+  it("Fails to create a task at another authority's address", async () => {
+    const victim = anchor.web3.Keypair.generate();
+    const [, victimPda] = await nextTaskPda(victim.publicKey);
+
+    try {
+      await program.methods
+        .createTask("Squatted", null, null)
+        .accounts({
+          taskAccount: victimPda,
+          userProfile: findProfilePda(user.publicKey)[0],
+          user: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed due to seed mismatch");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+  });
+
+  it("Migrates a task from the legacy seeds to the next profile ID", async () => {
+    // Seeded into the validator by Anchor.toml from tests/fixtures/legacy-task.json.
+    const legacyAuthority = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from(require("./fixtures/legacy-authority.json"))
+    );
+    await provider.connection.requestAirdrop(
+      legacyAuthority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL / 10
+    );
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    const legacyId = new BN(77);
+    const [legacyPda] = findLegacyTaskPda(legacyId);
+    // The fresh profile has not reached legacy ID 77, so the counter hands out ID 0 rather
+    // than jumping ahead to it.
+    const [migratedId, migratedPda] = await nextTaskPda(legacyAuthority.publicKey);
+
+    await program.methods
+      .migrateTaskSeeds(legacyId)
+      .accounts({
+        legacyTaskAccount: legacyPda,
+        userProfile: findProfilePda(legacyAuthority.publicKey)[0],
+        legacyIdSlot: findTaskPda(legacyAuthority.publicKey, legacyId)[0],
+        taskAccount: migratedPda,
+        authority: legacyAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const accountData = await program.account.taskAccount.fetch(migratedPda);
+    expect(accountData.id.eq(migratedId)).to.be.true;
+    expect(accountData.name).to.equal("Legacy Task");
+    expect(accountData.authority.equals(legacyAuthority.publicKey)).to.be.true;
+    expect(accountData.status).to.deep.equal({ todo: {} });
+    expect(await provider.connection.getAccountInfo(legacyPda)).to.be.null;
+
+    // New IDs must not collide with the migrated one.
+    const profile = await program.account.userProfile.fetch(
+      findProfilePda(legacyAuthority.publicKey)[0]
+    );
+    expect(profile.nextTaskId.eq(migratedId.addn(1))).to.be.true;
+    expect(profile.activeTaskCount.eqn(1)).to.be.true;
+  });
+
+  it("Migrates a legacy task to a new ID when its legacy ID is taken", async () => {
+    // Seeded into the validator by Anchor.toml from tests/fixtures/colliding-legacy-task.json.
+    const authority = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from(require("./fixtures/colliding-authority.json"))
+    );
+    await provider.connection.requestAirdrop(authority.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    // Tasks 0 and 1 come from the profile counter, so legacy ID 1 is already in use.
+    for (const name of ["First Task", "Second Task"]) {
+      const [, pda] = await nextTaskPda(authority.publicKey);
+      await program.methods
+        .createTask(name, null, null)
+        .accounts({
+          taskAccount: pda,
+          userProfile: findProfilePda(authority.publicKey)[0],
+          user: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    }
+
+    const legacyId = new BN(1);
+    const [legacyPda] = findLegacyTaskPda(legacyId);
+    const [, migratedPda] = await nextTaskPda(authority.publicKey);
+    await program.methods
+      .migrateTaskSeeds(legacyId)
+      .accounts({
+        legacyTaskAccount: legacyPda,
+        userProfile: findProfilePda(authority.publicKey)[0],
+        legacyIdSlot: findTaskPda(authority.publicKey, legacyId)[0],
+        taskAccount: migratedPda,
+        authority: authority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const accountData = await program.account.taskAccount.fetch(migratedPda);
+    expect(accountData.id.eqn(2)).to.be.true;
+    expect(accountData.name).to.equal("Colliding Legacy Task");
+    expect((await program.account.taskAccount.fetch(findTaskPda(authority.publicKey, legacyId)[0])).name).to.equal(
+      "Second Task"
+    );
+    const profile = await program.account.userProfile.fetch(findProfilePda(authority.publicKey)[0]);
+    expect(profile.nextTaskId.eqn(3)).to.be.true;
+  });
+
+  it("Keeps profile task counts in sync", async () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const before = await program.account.userProfile.fetch(profilePda);
+
+    const [countedId, countedPda] = await nextTaskPda(user.publicKey);
+    await program.methods
+      .createTask("Counted Task", null, null)
+      .accounts({
+        taskAccount: countedPda,
+        userProfile: profilePda,
+        user: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    let profile = await program.account.userProfile.fetch(profilePda);
+    expect(profile.nextTaskId.eq(countedId.addn(1))).to.be.true;
+    expect(profile.activeTaskCount.eq(before.activeTaskCount.addn(1))).to.be.true;
+    expect(profile.inactiveTaskCount.eq(before.inactiveTaskCount)).to.be.true;
+
+    await program.methods
+      .updateTaskStatus(false, null)
+      .accounts({
+        taskAccount: countedPda,
+        userProfile: profilePda,
+        authority: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+    // Setting the same status twice must not double count.
+    await program.methods
+      .updateTaskStatus(false, null)
+      .accounts({
+        taskAccount: countedPda,
+        userProfile: profilePda,
+        authority: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    profile = await program.account.userProfile.fetch(profilePda);
+    expect(profile.activeTaskCount.eq(before.activeTaskCount)).to.be.true;
+    expect(profile.inactiveTaskCount.eq(before.inactiveTaskCount.addn(1))).to.be.true;
+
+    await program.methods
+      .deleteTask()
+      .accounts({
+        taskAccount: countedPda,
+        userProfile: profilePda,
+        taskDescription: findDescriptionPda(countedPda)[0],
+        taskChecklist: findChecklistPda(countedPda)[0],
+        bountyVault: findBountyPda(countedPda)[0],
+        authority: user.publicKey,
+        authoritySigner: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    profile = await program.account.userProfile.fetch(profilePda);
+    expect(profile.activeTaskCount.eq(before.activeTaskCount)).to.be.true;
+    expect(profile.inactiveTaskCount.eq(before.inactiveTaskCount)).to.be.true;
+    expect(profile.nextTaskId.eq(countedId.addn(1))).to.be.true;
+  });
+
+  it("Walks a task through the full workflow", async () => {
+    const workflowPda = await createTask("Workflow Task");
+
+    const steps = [
+      { inProgress: {} },
+      { blocked: {} },
+      { inProgress: {} },
+      { inReview: {} },
+      { done: {} },
+      { archived: {} },
+    ];
+    for (const status of steps) {
+      await program.methods
+        .transitionTask(status as any, null)
+        .accounts({
+          taskAccount: workflowPda,
+          userProfile: findProfilePda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      const accountData = await program.account.taskAccount.fetch(workflowPda);
+      expect(accountData.status).to.deep.equal(status);
+    }
+
+    try {
+      await program.methods
+        .updateTaskStatus(true, null)
+        .accounts({
+          taskAccount: workflowPda,
+          userProfile: findProfilePda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed because the task is archived");
+    } catch (error) {
+      expect(error.toString()).to.include("TaskArchived");
+    }
+  });
+
+  it("Rejects illegal status transitions", async () => {
+    const illegalPda = await createTask("Illegal Transition Task");
+
+    for (const status of [{ done: {} }, { inReview: {} }, { blocked: {} }, { todo: {} }]) {
+      try {
+        await program.methods
+          .transitionTask(status as any, null)
+          .accounts({
+            taskAccount: illegalPda,
+            userProfile: findProfilePda(user.publicKey)[0],
+            authority: user.publicKey,
+          })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to illegal transition");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidStatusTransition");
+      }
+    }
+
+    const accountData = await program.account.taskAccount.fetch(illegalPda);
+    expect(accountData.status).to.deep.equal({ todo: {} });
+  });
+
+  it("Renames a task in place, resizing the account", async () => {
+    const [renameId, renamePda] = await nextTaskPda(user.publicKey);
+    await program.methods
+      .createTask("Short", null, null)
+      .accounts({
+        taskAccount: renamePda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        user: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+    const sizeBefore = (await provider.connection.getAccountInfo(renamePda)).data.length;
+
+    const longName = "A much longer name for the same task";
+    await program.methods
+      .updateTask({ name: longName, expectedRevision: null })
+      .accounts({
+        taskAccount: renamePda,
+        taskAuthority: user.publicKey,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    let info = await provider.connection.getAccountInfo(renamePda);
+    expect(info.data.length).to.equal(sizeBefore + longName.length - "Short".length);
+    let accountData = await program.account.taskAccount.fetch(renamePda);
+    expect(accountData.name).to.equal(longName);
+    expect(accountData.id.eq(renameId)).to.be.true;
+    const lamportsGrown = info.lamports;
+
+    await program.methods
+      .updateTask({ name: "Tiny", expectedRevision: null })
+      .accounts({
+        taskAccount: renamePda,
+        taskAuthority: user.publicKey,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    info = await provider.connection.getAccountInfo(renamePda);
+    expect(info.data.length).to.equal(sizeBefore - 1);
+    expect(info.lamports).to.be.lessThan(lamportsGrown);
+    accountData = await program.account.taskAccount.fetch(renamePda);
+    expect(accountData.name).to.equal("Tiny");
+
+    // Leaving the name out keeps it and the account size unchanged.
+    await program.methods
+      .updateTask({ name: null, expectedRevision: null })
+      .accounts({
+        taskAccount: renamePda,
+        taskAuthority: user.publicKey,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+    expect((await program.account.taskAccount.fetch(renamePda)).name).to.equal("Tiny");
+    expect((await provider.connection.getAccountInfo(renamePda)).data.length).to.equal(
+      sizeBefore - 1
+    );
+  });
+
+  it("Fails to rename a task with an invalid name or wrong authority", async () => {
+    const renamePda = await createTask("Rename Guard");
+
+    try {
+      await program.methods
+        .updateTask({ name: "任".repeat(17), expectedRevision: null })
+        .accounts({
+          taskAccount: renamePda,
+          taskAuthority: user.publicKey,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed due to name too long");
+    } catch (error) {
+      expect(error.toString()).to.include("NameTooLong");
+    }
+
+    const anotherUser = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(
+      anotherUser.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL / 10
+    );
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    try {
+      await program.methods
+        .updateTask({ name: "Hijacked", expectedRevision: null })
+        .accounts({
+          taskAccount: renamePda,
+          taskAuthority: user.publicKey,
+          authority: anotherUser.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([anotherUser])
+        .rpc();
+      expect.fail("Should have failed due to unauthorized action");
+    } catch (error) {
+      expect(error).to.be.an("error");
+    }
+    expect((await program.account.taskAccount.fetch(renamePda)).name).to.equal("Rename Guard");
+  });
+
+  describe("name validation", () => {
+    // The configured max name length is 50 bytes of UTF-8, not 50 characters.
+    const cases: { label: string; name: string; error?: string }[] = [
+      { label: "50 ASCII bytes", name: "A".repeat(50) },
+      { label: "51 ASCII bytes", name: "A".repeat(51), error: "NameTooLong" },
+      { label: "16 CJK characters (48 bytes)", name: "任".repeat(16) },
+      { label: "17 CJK characters (51 bytes)", name: "任".repeat(17), error: "NameTooLong" },
+      { label: "50 CJK characters (150 bytes)", name: "任".repeat(50), error: "NameTooLong" },
+      { label: "12 emoji (48 bytes)", name: "🚀".repeat(12) },
+      { label: "13 emoji (52 bytes)", name: "🚀".repeat(13), error: "NameTooLong" },
+      { label: "25 two-byte characters (50 bytes)", name: "é".repeat(25) },
+      { label: "26 two-byte characters (52 bytes)", name: "é".repeat(26), error: "NameTooLong" },
+      { label: "mixed scripts", name: "Fix 🐛 in 任务 queue" },
+      { label: "empty", name: "", error: "NameEmpty" },
+      { label: "newline", name: "line\nbreak", error: "NameContainsControlCharacters" },
+      { label: "NUL byte", name: "nul\u0000", error: "NameContainsControlCharacters" },
+      { label: "C1 control", name: "c1\u0085", error: "NameContainsControlCharacters" },
+    ];
+
+    for (const { label, name, error } of cases) {
+      it(`${error ? "Rejects" : "Accepts"} a name with ${label}`, async () => {
+        if (error) {
+          try {
+            await createTask(name);
+            expect.fail(`Should have failed with ${error}`);
+          } catch (e) {
+            expect(e.toString()).to.include(error);
+          }
+        } else {
+          const namePda = await createTask(name);
+          const accountData = await program.account.taskAccount.fetch(namePda);
+          expect(accountData.name).to.equal(name);
+        }
+      });
+    }
+  });
+
+  it("Writes a long description in chunks and closes it with the task", async () => {
+    const describedPda = await createTask("Described Task");
+    const [descriptionPda] = findDescriptionPda(describedPda);
+
+    const content = Buffer.from(
+      "## Acceptance criteria\n" + "- links and notes ✅\n".repeat(150)
+    );
+    expect(content.length).to.be.greaterThan(3000);
+    await program.methods
+      .initTaskDescription(content.length)
+      .accounts({
+        taskAccount: describedPda,
+        taskDescription: descriptionPda,
+        taskAuthority: user.publicKey,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    const writeChunk = (offset: number, chunk: Buffer) =>
+      program.methods
+        .writeTaskDescription(offset, chunk)
+        .accounts({
+          taskAccount: describedPda,
+          taskDescription: descriptionPda,
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const chunkSize = 800;
+    for (let offset = 0; offset < content.length; offset += chunkSize) {
+      await writeChunk(offset, content.subarray(offset, offset + chunkSize));
+    }
+
+    // Replaying a chunk at a stale offset must not duplicate content.
+    try {
+      await writeChunk(0, content.subarray(0, chunkSize));
+      expect.fail("Should have failed due to offset mismatch");
+    } catch (error) {
+      expect(error.toString()).to.include("DescriptionOffsetMismatch");
+    }
+
+    await program.methods
+      .finalizeTaskDescription()
+      .accounts({
+        taskAccount: describedPda,
+        taskDescription: descriptionPda,
+        authority: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    const description = await program.account.taskDescription.fetch(descriptionPda);
+    expect(Buffer.from(description.content).equals(content)).to.be.true;
+    expect(description.finalized).to.be.true;
+    const accountData = await program.account.taskAccount.fetch(describedPda);
+    const expectedHash = require("crypto").createHash("sha256").update(content).digest();
+    expect(Buffer.from(accountData.descriptionHash).equals(expectedHash)).to.be.true;
+
+    try {
+      await writeChunk(content.length, Buffer.from("more"));
+      expect.fail("Should have failed because the description is finalized");
+    } catch (error) {
+      expect(error.toString()).to.include("DescriptionFinalized");
+    }
+
+    const descriptionRent = await provider.connection.getBalance(descriptionPda);
+    const balanceBefore = await provider.connection.getBalance(user.publicKey);
+    await program.methods
+      .deleteTask()
+      .accounts({
+        taskAccount: describedPda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        taskDescription: descriptionPda,
+        taskChecklist: findChecklistPda(describedPda)[0],
+        bountyVault: findBountyPda(describedPda)[0],
+        authority: user.publicKey,
+        authoritySigner: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+    const balanceAfter = await provider.connection.getBalance(user.publicKey);
+
+    expect(await provider.connection.getAccountInfo(descriptionPda)).to.be.null;
+    expect(balanceAfter - balanceBefore).to.be.greaterThan(descriptionRent);
+  });
+
+  it("Rejects description chunks beyond the reserved length", async () => {
+    const describedPda = await createTask("Small Description");
+    const [descriptionPda] = findDescriptionPda(describedPda);
+    await program.methods
+      .initTaskDescription(8)
+      .accounts({
+        taskAccount: describedPda,
+        taskDescription: descriptionPda,
+        taskAuthority: user.publicKey,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    try {
+      await program.methods
+        .writeTaskDescription(0, Buffer.from("nine byte"))
+        .accounts({
+          taskAccount: describedPda,
+          taskDescription: descriptionPda,
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed due to description too long");
+    } catch (error) {
+      expect(error.toString()).to.include("DescriptionTooLong");
+    }
+  });
+
+  describe("schedule", () => {
+    // Reads unix_timestamp from the Clock sysvar so the tests follow the validator's clock.
+    const now = async () => {
+      const clock = await provider.connection.getAccountInfo(anchor.web3.SYSVAR_CLOCK_PUBKEY);
+      return new BN(clock.data.subarray(32, 40), "le").toNumber();
+    };
+
+    const isOverdue = (pda: anchor.web3.PublicKey) =>
+      program.methods.isTaskOverdue().accounts({ taskAccount: pda }).view();
+
+    it("Stores start and due dates", async () => {
+      const startAt = new BN((await now()) + 60);
+      const dueAt = new BN((await now()) + 3600);
+      const pda = await createTask("Scheduled Task", { startAt, dueAt });
+
+      const accountData = await program.account.taskAccount.fetch(pda);
+      expect(accountData.startAt.eq(startAt)).to.be.true;
+      expect(accountData.dueAt.eq(dueAt)).to.be.true;
+      expect(accountData.completedAt).to.be.null;
+      expect(await isOverdue(pda)).to.be.false;
+    });
+
+    it("Rejects a due date in the past", async () => {
+      try {
+        await createTask("Late Task", { dueAt: new BN((await now()) - 60) });
+        expect.fail("Should have failed due to due date in the past");
+      } catch (error) {
+        expect(error.toString()).to.include("DueDateInPast");
+      }
+    });
+
+    it("Rejects a start date after the due date", async () => {
+      const dueAt = (await now()) + 60;
+      try {
+        await createTask("Backwards Task", { startAt: new BN(dueAt + 1), dueAt: new BN(dueAt) });
+        expect.fail("Should have failed due to start after due");
+      } catch (error) {
+        expect(error.toString()).to.include("StartAfterDue");
+      }
+    });
+
+    it("Reports overdue tasks until they are done", async () => {
+      const pda = await createTask("Soon Due", { dueAt: new BN((await now()) + 2) });
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+      expect(await isOverdue(pda)).to.be.true;
+
+      await program.methods
+        .updateTaskStatus(false, null)
+        .accounts({
+          taskAccount: pda,
+          userProfile: findProfilePda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+
+      const accountData = await program.account.taskAccount.fetch(pda);
+      expect(accountData.completedAt).to.not.be.null;
+      expect(await isOverdue(pda)).to.be.false;
+    });
+
+    it("Records completion time on Done and clears it when reopened", async () => {
+      const pda = await createTask("Completion Task");
+      const transition = (status: object) =>
+        program.methods
+          .transitionTask(status as any, null)
+          .accounts({
+            taskAccount: pda,
+            userProfile: findProfilePda(user.publicKey)[0],
+            authority: user.publicKey,
+          })
+          .signers([user.payer])
+          .rpc();
+
+      await transition({ inProgress: {} });
+      await transition({ inReview: {} });
+      await transition({ done: {} });
+      let accountData = await program.account.taskAccount.fetch(pda);
+      expect(accountData.completedAt.toNumber()).to.be.greaterThan(0);
+
+      await transition({ inProgress: {} });
+      accountData = await program.account.taskAccount.fetch(pda);
+      expect(accountData.completedAt).to.be.null;
+    });
+  });
+
+  it("Tracks timestamps and revisions with optimistic concurrency", async () => {
+    const revisedPda = await createTask("Revised Task");
+
+    let accountData = await program.account.taskAccount.fetch(revisedPda);
+    expect(accountData.revision.eqn(0)).to.be.true;
+    expect(accountData.createdAt.toNumber()).to.be.greaterThan(0);
+    expect(accountData.updatedAt.eq(accountData.createdAt)).to.be.true;
+
+    await program.methods
+      .transitionTask({ inProgress: {} } as any, new BN(0))
+      .accounts({
+        taskAccount: revisedPda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        authority: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+    await program.methods
+      .updateTask({ name: "Revised Again", expectedRevision: new BN(1) })
+      .accounts({
+        taskAccount: revisedPda,
+        taskAuthority: user.publicKey,
+        authority: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+    await program.methods
+      .updateTaskStatus(false, new BN(2))
+      .accounts({
+        taskAccount: revisedPda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        authority: user.publicKey,
+      })
+      .signers([user.payer])
+      .rpc();
+
+    accountData = await program.account.taskAccount.fetch(revisedPda);
+    expect(accountData.revision.eqn(3)).to.be.true;
+    expect(accountData.updatedAt.gte(accountData.createdAt)).to.be.true;
+
+    // A client still holding revision 1 must not overwrite the newer state.
+    try {
+      await program.methods
+        .updateTask({ name: "Stale Write", expectedRevision: new BN(1) })
+        .accounts({
+          taskAccount: revisedPda,
+          taskAuthority: user.publicKey,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed due to stale revision");
+    } catch (error) {
+      expect(error.toString()).to.include("StaleRevision");
+    }
+    try {
+      await program.methods
+        .transitionTask({ inProgress: {} } as any, new BN(2))
+        .accounts({
+          taskAccount: revisedPda,
+          userProfile: findProfilePda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed due to stale revision");
+    } catch (error) {
+      expect(error.toString()).to.include("StaleRevision");
+    }
+    try {
+      await program.methods
+        .updateTaskStatus(true, new BN(2))
+        .accounts({
+          taskAccount: revisedPda,
+          userProfile: findProfilePda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed due to stale revision");
+    } catch (error) {
+      expect(error.toString()).to.include("StaleRevision");
+    }
+
+    accountData = await program.account.taskAccount.fetch(revisedPda);
+    expect(accountData.name).to.equal("Revised Again");
+    expect(accountData.revision.eqn(3)).to.be.true;
+  });
+
+  it("Migrates a legacy-layout task in place", async () => {
+    // Seeded into the validator by Anchor.toml from tests/fixtures/legacy-layout-task.json:
+    // the unversioned layout (id, name, authority, active) at a namespaced address.
+    const legacyAuthority = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from(require("./fixtures/legacy-authority.json"))
+    );
+    await provider.connection.requestAirdrop(
+      legacyAuthority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL / 10
+    );
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    const legacyId = new BN(78);
+    const [legacyLayoutPda] = findTaskPda(legacyAuthority.publicKey, legacyId);
+    const before = await provider.connection.getAccountInfo(legacyLayoutPda);
+    expect(before.data.length).to.equal(103);
+
+    // The current layout cannot be read until the account is migrated.
+    try {
+      await program.account.taskAccount.fetch(legacyLayoutPda);
+      expect.fail("Legacy layout should not decode as the current layout");
+    } catch (error) {
+      expect(error).to.be.an("error");
+    }
+
+    const migrate = () =>
+      program.methods
+        .migrateTask(legacyId)
+        .accounts({
+          taskAccount: legacyLayoutPda,
+          authority: legacyAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([legacyAuthority])
+        .rpc();
+    await migrate();
+
+    const accountData = await program.account.taskAccount.fetch(legacyLayoutPda);
+    expect(accountData.version).to.equal(10);
+    expect(accountData.id.eq(legacyId)).to.be.true;
+    expect(accountData.name).to.equal("Legacy Layout Task");
+    expect(accountData.authority.equals(legacyAuthority.publicKey)).to.be.true;
+    expect(accountData.status).to.deep.equal({ todo: {} });
+    expect(accountData.revision.eqn(0)).to.be.true;
+    expect(accountData.createdAt.toNumber()).to.be.greaterThan(0);
+
+    const after = await provider.connection.getAccountInfo(legacyLayoutPda);
+    expect(after.data.length).to.be.greaterThan(before.data.length);
+    expect(after.lamports).to.equal(
+      await provider.connection.getMinimumBalanceForRentExemption(after.data.length)
+    );
+
+    try {
+      await migrate();
+      expect.fail("Should have failed because the task is already migrated");
+    } catch (error) {
+      expect(error.toString()).to.include("TaskAlreadyMigrated");
+    }
+  });
+
+  it("Creates tasks at the current layout version and refuses to re-migrate them", async () => {
+    const [versionedId, versionedPda] = await nextTaskPda(user.publicKey);
+    await program.methods
+      .createTask("Versioned Task", null, null)
+      .accounts({
+        taskAccount: versionedPda,
+        userProfile: findProfilePda(user.publicKey)[0],
+        user: user.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user.payer])
+      .rpc();
+    expect((await program.account.taskAccount.fetch(versionedPda)).version).to.equal(10);
+
+    try {
+      await program.methods
+        .migrateTask(versionedId)
+        .accounts({
+          taskAccount: versionedPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect.fail("Should have failed because the task is already migrated");
+    } catch (error) {
+      expect(error.toString()).to.include("TaskAlreadyMigrated");
+    }
+  });
+
+  it("Migrates a version 1 task to the fixed-offset layout", async () => {
+    // Seeded into the validator by Anchor.toml from tests/fixtures/v1-layout-task.json.
+    const legacyAuthority = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from(require("./fixtures/legacy-authority.json"))
+    );
+    await provider.connection.requestAirdrop(
+      legacyAuthority.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL / 10
+    );
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    const v1Id = new BN(79);
+    const [v1Pda] = findTaskPda(legacyAuthority.publicKey, v1Id);
+    await program.methods
+      .migrateTask(v1Id)
+      .accounts({
+        taskAccount: v1Pda,
+        authority: legacyAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const accountData = await program.account.taskAccount.fetch(v1Pda);
+    expect(accountData.version).to.equal(10);
+    expect(accountData.name).to.equal("V1 Layout Task");
+    expect(accountData.status).to.deep.equal({ inProgress: {} });
+    expect(accountData.startAt.toNumber()).to.equal(1_700_000_000);
+    expect(accountData.dueAt.toNumber()).to.equal(4_000_000_000);
+    expect(accountData.completedAt).to.be.null;
+    expect(accountData.descriptionHash).to.be.null;
+    expect(accountData.createdAt.toNumber()).to.equal(1_690_000_000);
+    expect(accountData.updatedAt.toNumber()).to.equal(1_695_000_000);
+    expect(accountData.revision.eqn(7)).to.be.true;
+
+    const info = await provider.connection.getAccountInfo(v1Pda);
+    expect(info.data.readUInt8(8)).to.equal(10);
+    expect(new anchor.web3.PublicKey(info.data.subarray(17, 49)).equals(legacyAuthority.publicKey)).to.be
+      .true;
+  });
+
+  it("Ignores stale bytes past an older layout when migrating", async () => {
+    // Seeded into the validator by Anchor.toml from tests/fixtures/v9-layout-task.json: a version
+    // 9 task followed by leftover bytes that would read as a set `approval_policy`.
+    const legacyAuthority = anchor.web3.Keypair.fromSecretKey(
+      Uint8Array.from(require("./fixtures/legacy-authority.json"))
+    );
+    const v9Id = new BN(80);
+    const [v9Pda] = findTaskPda(legacyAuthority.publicKey, v9Id);
+
+    try {
+      await program.methods
+        .setTaskPriority({ low: {} }, null)
+        .accounts({ taskAccount: v9Pda, authority: legacyAuthority.publicKey })
+        .signers([legacyAuthority])
+        .rpc();
+      expect.fail("Should have failed because the task is not migrated");
+    } catch (error) {
+      expect(error.toString()).to.include("TaskNotMigrated");
+    }
+
+    await program.methods
+      .migrateTask(v9Id)
+      .accounts({
+        taskAccount: v9Pda,
+        authority: legacyAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const accountData = await program.account.taskAccount.fetch(v9Pda);
+    expect(accountData.version).to.equal(10);
+    expect(accountData.name).to.equal("V9 Stale Task");
+    expect(accountData.priority).to.deep.equal({ high: {} });
+    expect(accountData.creator.equals(legacyAuthority.publicKey)).to.be.true;
+    expect(accountData.approvalPolicy).to.be.null;
+  });
+
+  it("Lists an authority's tasks with a memcmp filter", async () => {
+    const authorityOffset = 17;
+    const statusOffset = 49;
+    const tasks = await program.account.taskAccount.all([
+      { memcmp: { offset: authorityOffset, bytes: user.publicKey.toBase58() } },
+    ]);
+    expect(tasks.length).to.be.greaterThan(0);
+    for (const task of tasks) {
+      expect(task.account.authority.equals(user.publicKey)).to.be.true;
+    }
+
+    // Filters compose: only the authority's tasks that are still Todo (status byte 0).
+    const todo = await program.account.taskAccount.all([
+      { memcmp: { offset: authorityOffset, bytes: user.publicKey.toBase58() } },
+      { memcmp: { offset: statusOffset, bytes: anchor.utils.bytes.bs58.encode([0]) } },
+    ]);
+    expect(todo.length).to.be.greaterThan(0);
+    expect(todo.length).to.be.lessThan(tasks.length);
+    for (const task of todo) {
+      expect(task.account.status).to.deep.equal({ todo: {} });
+    }
+  });
+
+  describe("priority and ordering", () => {
+    const reorder = (
+      task: anchor.web3.PublicKey,
+      prevTask: anchor.web3.PublicKey | null,
+      nextTask: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .reorderTask(null)
+        .accounts({
+          taskAccount: task,
+          prevTask,
+          nextTask,
+          userProfile: findProfilePda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const rankOf = async (pda: anchor.web3.PublicKey) =>
+      (await program.account.taskAccount.fetch(pda)).rank;
+
+    it("Sets a task's priority", async () => {
+      const pda = await createTask("Prioritized Task");
+      expect((await program.account.taskAccount.fetch(pda)).priority).to.deep.equal({ low: {} });
+
+      await program.methods
+        .setTaskPriority({ critical: {} } as any, null)
+        .accounts({ taskAccount: pda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      const accountData = await program.account.taskAccount.fetch(pda);
+      expect(accountData.priority).to.deep.equal({ critical: {} });
+      expect(accountData.revision.eqn(1)).to.be.true;
+
+      try {
+        await program.methods
+          .setTaskPriority({ high: {} } as any, new BN(0))
+          .accounts({ taskAccount: pda, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to stale revision");
+      } catch (error) {
+        expect(error.toString()).to.include("StaleRevision");
+      }
+    });
+
+    it("Appends new tasks and reorders them between neighbours", async () => {
+      const a = await createTask("Order A");
+      const b = await createTask("Order B");
+      const c = await createTask("Order C");
+      expect((await rankOf(a)).lt(await rankOf(b))).to.be.true;
+      expect((await rankOf(b)).lt(await rankOf(c))).to.be.true;
+
+      // Move C between A and B.
+      await reorder(c, a, b);
+      let rankC = await rankOf(c);
+      expect(rankC.gt(await rankOf(a))).to.be.true;
+      expect(rankC.lt(await rankOf(b))).to.be.true;
+
+      // Move A to the end, after B.
+      await reorder(a, b, null);
+      expect((await rankOf(a)).gt(await rankOf(b))).to.be.true;
+
+      // Move B to the front, before C.
+      await reorder(b, null, c);
+      expect((await rankOf(b)).lt(await rankOf(c))).to.be.true;
+
+      // Final order is B, C, A.
+      const [rankA, rankB] = [await rankOf(a), await rankOf(b)];
+      rankC = await rankOf(c);
+      expect(rankB.lt(rankC)).to.be.true;
+      expect(rankC.lt(rankA)).to.be.true;
+    });
+
+    it("Appends new tasks after a task moved to the end", async () => {
+      const a = await createTask("Append A");
+      const b = await createTask("Append B");
+      await reorder(a, b, null);
+      const c = await createTask("Append C");
+
+      const [rankA, rankB, rankC] = [await rankOf(a), await rankOf(b), await rankOf(c)];
+      expect(rankB.lt(rankA)).to.be.true;
+      expect(rankA.lt(rankC)).to.be.true;
+    });
+
+    it("Rejects neighbours that are out of order or not the caller's", async () => {
+      const a = await createTask("Neighbour A");
+      const b = await createTask("Neighbour B");
+      const c = await createTask("Neighbour C");
+
+      try {
+        await reorder(c, b, a);
+        expect.fail("Should have failed due to out-of-order neighbours");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNeighbours");
+      }
+      try {
+        await reorder(c, c, null);
+        expect.fail("Should have failed because a task cannot neighbour itself");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNeighbours");
+      }
+      try {
+        await reorder(c, null, null);
+        expect.fail("Should have failed without neighbours");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNeighbours");
+      }
+
+      const legacyAuthority = anchor.web3.Keypair.fromSecretKey(
+        Uint8Array.from(require("./fixtures/legacy-authority.json"))
+      );
+      const [foreignPda] = findTaskPda(legacyAuthority.publicKey, new BN(77));
+      try {
+        await reorder(c, foreignPda, null);
+        expect.fail("Should have failed due to a foreign neighbour");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNeighbours");
+      }
+    });
+  });
+
+  describe("tags", () => {
+    const [registryPda] = findTagRegistryPda(user.publicKey);
+    let taggedPda: anchor.web3.PublicKey;
+
+    const createTag = (name: string) =>
+      program.methods
+        .createTag(name)
+        .accounts({
+          tagRegistry: registryPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const tagTask = (method: "addTaskTag" | "removeTaskTag", index: number) =>
+      program.methods[method](index, null)
+        .accounts({
+          taskAccount: taggedPda,
+          tagRegistry: registryPda,
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const tagIndex = async (name: string) =>
+      (await program.account.tagRegistry.fetch(registryPda)).tags.findIndex(
+        (tag) => tag.name === name
+      );
+
+    before(async () => {
+      await createTag("bug");
+      await createTag("infra");
+      await createTag("frontend");
+
+      taggedPda = await createTask("Tagged Task");
+    });
+
+    it("Creates tags and rejects duplicate names", async () => {
+      const registry = await program.account.tagRegistry.fetch(registryPda);
+      expect(registry.tags.map((tag) => tag.name)).to.include.members(["bug", "infra", "frontend"]);
+
+      try {
+        await createTag("bug");
+        expect.fail("Should have failed due to duplicate tag name");
+      } catch (error) {
+        expect(error.toString()).to.include("TagNameTaken");
+      }
+      try {
+        await createTag("a-very-long-tag-name");
+        expect.fail("Should have failed due to tag name too long");
+      } catch (error) {
+        expect(error.toString()).to.include("NameTooLong");
+      }
+    });
+
+    it("Adds and removes tags on a task", async () => {
+      const bug = await tagIndex("bug");
+      const infra = await tagIndex("infra");
+      await tagTask("addTaskTag", bug);
+      await tagTask("addTaskTag", infra);
+      let accountData = await program.account.taskAccount.fetch(taggedPda);
+      expect(accountData.tags).to.equal((1 << bug) | (1 << infra));
+
+      await tagTask("removeTaskTag", bug);
+      accountData = await program.account.taskAccount.fetch(taggedPda);
+      expect(accountData.tags).to.equal(1 << infra);
+
+      try {
+        await tagTask("addTaskTag", 31);
+        expect.fail("Should have failed because the tag does not exist");
+      } catch (error) {
+        expect(error.toString()).to.include("TagNotFound");
+      }
+    });
+
+    it("Renames a tag without touching tasks", async () => {
+      const infra = await tagIndex("infra");
+      await program.methods
+        .renameTag(infra, "platform")
+        .accounts({ tagRegistry: registryPda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+
+      expect(await tagIndex("platform")).to.equal(infra);
+      expect((await program.account.taskAccount.fetch(taggedPda)).tags).to.equal(1 << infra);
+    });
+
+    it("Retires a tag while tasks keep its bit", async () => {
+      const frontend = await tagIndex("frontend");
+      await tagTask("addTaskTag", frontend);
+      await program.methods
+        .retireTag(frontend)
+        .accounts({ tagRegistry: registryPda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+
+      const registry = await program.account.tagRegistry.fetch(registryPda);
+      expect(registry.tags[frontend].retired).to.be.true;
+      expect((await program.account.taskAccount.fetch(taggedPda)).tags & (1 << frontend)).to.not.equal(0);
+
+      // The retired bit is never handed out again.
+      await createTag("frontend-v2");
+      expect(await tagIndex("frontend-v2")).to.not.equal(frontend);
+
+      try {
+        await program.methods
+          .renameTag(frontend, "revived")
+          .accounts({ tagRegistry: registryPda, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed because the tag is retired");
+      } catch (error) {
+        expect(error.toString()).to.include("TagRetired");
+      }
+
+      // A retired tag can still be removed from a task, but not added back.
+      await tagTask("removeTaskTag", frontend);
+      try {
+        await tagTask("addTaskTag", frontend);
+        expect.fail("Should have failed because the tag is retired");
+      } catch (error) {
+        expect(error.toString()).to.include("TagRetired");
+      }
+    });
+  });
+
+  describe("subtasks", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    let parentPda: anchor.web3.PublicKey;
+    let firstChildPda: anchor.web3.PublicKey;
+    let secondChildPda: anchor.web3.PublicKey;
+
+    const setActive = (
+      task: anchor.web3.PublicKey,
+      active: boolean,
+      parentTask: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .updateTaskStatus(active, null)
+        .accounts({ taskAccount: task, userProfile: profilePda, parentTask, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+
+    const deleteAccounts = (task: anchor.web3.PublicKey, parentTask: anchor.web3.PublicKey | null) => ({
+      taskAccount: task,
+      userProfile: profilePda,
+      taskDescription: findDescriptionPda(task)[0],
+      taskChecklist: findChecklistPda(task)[0],
+      bountyVault: findBountyPda(task)[0],
+      parentTask,
+      authority: user.publicKey,
+      authoritySigner: user.publicKey,
+    });
+
+    before(async () => {
+      parentPda = await createTask("Parent Task");
+      firstChildPda = await createTask("First Subtask", { parentTask: parentPda });
+      secondChildPda = await createTask("Second Subtask", { parentTask: parentPda });
+    });
+
+    it("Creates subtasks under a parent", async () => {
+      const parent = await program.account.taskAccount.fetch(parentPda);
+      expect(parent.childCount).to.equal(2);
+      expect(parent.openChildCount).to.equal(2);
+      const child = await program.account.taskAccount.fetch(firstChildPda);
+      expect(child.parent.equals(parentPda)).to.be.true;
+    });
+
+    it("Refuses to close a parent with open subtasks", async () => {
+      for (const status of [{ inProgress: {} }, { inReview: {} }]) {
+        await program.methods
+          .transitionTask(status as any, null)
+          .accounts({ taskAccount: parentPda, userProfile: profilePda, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+      }
+      try {
+        await program.methods
+          .transitionTask({ done: {} } as any, null)
+          .accounts({ taskAccount: parentPda, userProfile: profilePda, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to open subtasks");
+      } catch (error) {
+        expect(error.toString()).to.include("OpenSubtasks");
+      }
+      try {
+        await setActive(parentPda, false, null);
+        expect.fail("Should have failed due to open subtasks");
+      } catch (error) {
+        expect(error.toString()).to.include("OpenSubtasks");
+      }
+    });
+
+    it("Requires the parent when a subtask changes status", async () => {
+      try {
+        await setActive(firstChildPda, false, null);
+        expect.fail("Should have failed without the parent account");
+      } catch (error) {
+        expect(error.toString()).to.include("ParentTaskMismatch");
+      }
+
+      await setActive(firstChildPda, false, parentPda);
+      const parent = await program.account.taskAccount.fetch(parentPda);
+      expect(parent.openChildCount).to.equal(1);
+    });
+
+    it("Refuses to delete a parent that still has subtasks", async () => {
+      try {
+        await program.methods
+          .deleteTask()
+          .accounts(deleteAccounts(parentPda, null))
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to subtasks");
+      } catch (error) {
+        expect(error.toString()).to.include("HasSubtasks");
+      }
+    });
+
+    it("Deletes a subtask and lets the parent complete", async () => {
+      await program.methods
+        .deleteTask()
+        .accounts(deleteAccounts(secondChildPda, parentPda))
+        .signers([user.payer])
+        .rpc();
+      let parent = await program.account.taskAccount.fetch(parentPda);
+      expect(parent.childCount).to.equal(1);
+      expect(parent.openChildCount).to.equal(0);
+
+      await program.methods
+        .transitionTask({ done: {} } as any, null)
+        .accounts({ taskAccount: parentPda, userProfile: profilePda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      parent = await program.account.taskAccount.fetch(parentPda);
+      expect(parent.status).to.deep.equal({ done: {} });
+
+      try {
+        await setActive(firstChildPda, true, parentPda);
+        expect.fail("Should have failed because the parent is closed");
+      } catch (error) {
+        expect(error.toString()).to.include("ParentTaskClosed");
+      }
+    });
+
+    it("Cascades a delete through the remaining subtasks", async () => {
+      try {
+        await program.methods
+          .deleteTaskCascade()
+          .accounts(deleteAccounts(parentPda, null))
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed because a subtask was not passed");
+      } catch (error) {
+        expect(error.toString()).to.include("HasSubtasks");
+      }
+
+      await program.methods
+        .deleteTaskCascade()
+        .accounts(deleteAccounts(parentPda, null))
+        .remainingAccounts([
+          { pubkey: firstChildPda, isWritable: true, isSigner: false },
+          { pubkey: findDescriptionPda(firstChildPda)[0], isWritable: true, isSigner: false },
+          { pubkey: findChecklistPda(firstChildPda)[0], isWritable: true, isSigner: false },
+          { pubkey: findBountyPda(firstChildPda)[0], isWritable: false, isSigner: false },
+        ])
+        .signers([user.payer])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(parentPda)).to.be.null;
+      expect(await provider.connection.getAccountInfo(firstChildPda)).to.be.null;
+    });
+  });
+
+  describe("dependencies", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    let blockerPda: anchor.web3.PublicKey;
+    let dependentPda: anchor.web3.PublicKey;
+
+    const account = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isWritable: false, isSigner: false });
+
+    const addDependency = (
+      task: anchor.web3.PublicKey,
+      dependsOn: anchor.web3.PublicKey,
+      remaining: anchor.web3.PublicKey[] = []
+    ) =>
+      program.methods
+        .addDependency(null)
+        .accounts({
+          taskAccount: task,
+          dependsOn,
+          taskDependency: findDependencyPda(task, dependsOn)[0],
+          taskAuthority: user.publicKey,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(remaining.map(account))
+        .signers([user.payer])
+        .rpc();
+
+    const transition = (task: anchor.web3.PublicKey, status: object, remaining: anchor.web3.PublicKey[] = []) =>
+      program.methods
+        .transitionTask(status as any, null)
+        .accounts({ taskAccount: task, userProfile: profilePda, authority: user.publicKey })
+        .remainingAccounts(remaining.map(account))
+        .signers([user.payer])
+        .rpc();
+
+    before(async () => {
+      blockerPda = await createTask("Blocking Task");
+      dependentPda = await createTask("Dependent Task");
+      await addDependency(dependentPda, blockerPda);
+    });
+
+    it("Records the dependency", async () => {
+      const dependency = await program.account.taskDependency.fetch(findDependencyPda(dependentPda, blockerPda)[0]);
+      expect(dependency.task.equals(dependentPda)).to.be.true;
+      expect(dependency.dependsOn.equals(blockerPda)).to.be.true;
+      const task = await program.account.taskAccount.fetch(dependentPda);
+      expect(task.dependencyCount).to.equal(1);
+    });
+
+    it("Rejects dependency cycles", async () => {
+      try {
+        await addDependency(blockerPda, blockerPda);
+        expect.fail("Should have failed due to a self-dependency");
+      } catch (error) {
+        expect(error.toString()).to.include("DependencyCycle");
+      }
+      try {
+        await addDependency(blockerPda, dependentPda);
+        expect.fail("Should have failed because the dependency graph was not passed");
+      } catch (error) {
+        expect(error.toString()).to.include("DependencyAccountsMismatch");
+      }
+      try {
+        await addDependency(blockerPda, dependentPda, [findDependencyPda(dependentPda, blockerPda)[0], blockerPda]);
+        expect.fail("Should have failed due to a dependency cycle");
+      } catch (error) {
+        expect(error.toString()).to.include("DependencyCycle");
+      }
+    });
+
+    it("Blocks starting a task while a dependency is open", async () => {
+      try {
+        await transition(dependentPda, { inProgress: {} });
+        expect.fail("Should have failed because the dependency was not passed");
+      } catch (error) {
+        expect(error.toString()).to.include("DependencyAccountsMismatch");
+      }
+      try {
+        await transition(dependentPda, { inProgress: {} }, [findDependencyPda(dependentPda, blockerPda)[0], blockerPda]);
+        expect.fail("Should have failed due to an open dependency");
+      } catch (error) {
+        expect(error.toString()).to.include("DependencyOpen");
+      }
+    });
+
+    it("Starts a task once its dependencies are done", async () => {
+      for (const status of [{ inProgress: {} }, { inReview: {} }, { done: {} }]) {
+        await transition(blockerPda, status);
+      }
+      await transition(dependentPda, { inProgress: {} }, [findDependencyPda(dependentPda, blockerPda)[0], blockerPda]);
+      const task = await program.account.taskAccount.fetch(dependentPda);
+      expect(task.status).to.deep.equal({ inProgress: {} });
+    });
+
+    it("Removes dependencies before deleting a task", async () => {
+      const deleteAccounts = {
+        taskAccount: dependentPda,
+        userProfile: profilePda,
+        taskDescription: findDescriptionPda(dependentPda)[0],
+        taskChecklist: findChecklistPda(dependentPda)[0],
+        bountyVault: findBountyPda(dependentPda)[0],
+        authority: user.publicKey,
+        authoritySigner: user.publicKey,
+      };
+      try {
+        await program.methods.deleteTask().accounts(deleteAccounts).signers([user.payer]).rpc();
+        expect.fail("Should have failed due to a remaining dependency");
+      } catch (error) {
+        expect(error.toString()).to.include("HasDependencies");
+      }
+
+      const [dependencyPda] = findDependencyPda(dependentPda, blockerPda);
+      await program.methods
+        .removeDependency(null)
+        .accounts({
+          taskAccount: dependentPda,
+          taskAuthority: user.publicKey,
+          dependsOn: blockerPda,
+          taskDependency: dependencyPda,
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(dependencyPda)).to.be.null;
+
+      await program.methods.deleteTask().accounts(deleteAccounts).signers([user.payer]).rpc();
+      expect(await provider.connection.getAccountInfo(dependentPda)).to.be.null;
+    });
+  });
+
+  describe("checklists", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    let checklistTaskPda: anchor.web3.PublicKey;
+    let checklistPda: anchor.web3.PublicKey;
+
+    const manageAccounts = () => ({
+      taskAccount: checklistTaskPda,
+      taskChecklist: checklistPda,
+      authority: user.publicKey,
+    });
+
+    const toggle = (index: number) =>
+      program.methods
+        .toggleChecklistItem(index, null)
+        .accounts({ ...manageAccounts(), userProfile: profilePda })
+        .signers([user.payer])
+        .rpc();
+
+    const completion = () =>
+      program.methods.checklistCompletion().accounts({ taskChecklist: checklistPda }).view();
+
+    before(async () => {
+      const pda = await createTask("Checklist Task");
+      checklistTaskPda = pda;
+      [checklistPda] = findChecklistPda(pda);
+      for (const text of ["Write draft", "Review", "Publish"]) {
+        await program.methods
+          .addChecklistItem(text, null)
+          .accounts({ ...manageAccounts(), taskAuthority: user.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+          .signers([user.payer])
+          .rpc();
+      }
+    });
+
+    it("Adds, edits and reorders items", async () => {
+      await program.methods
+        .editChecklistItem(1, "Peer review", null)
+        .accounts(manageAccounts())
+        .signers([user.payer])
+        .rpc();
+      await program.methods
+        .moveChecklistItem(2, 0, null)
+        .accounts(manageAccounts())
+        .signers([user.payer])
+        .rpc();
+
+      const checklist = await program.account.checklist.fetch(checklistPda);
+      expect(checklist.task.equals(checklistTaskPda)).to.be.true;
+      expect(checklist.items.map((item) => item.text)).to.deep.equal(["Publish", "Write draft", "Peer review"]);
+
+      try {
+        await program.methods
+          .moveChecklistItem(0, 3, null)
+          .accounts(manageAccounts())
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to an unknown item");
+      } catch (error) {
+        expect(error.toString()).to.include("ChecklistItemNotFound");
+      }
+    });
+
+    it("Derives the completion percentage", async () => {
+      expect(await completion()).to.equal(0);
+      await toggle(0);
+      expect(await completion()).to.equal(33);
+      await toggle(0);
+      expect(await completion()).to.equal(0);
+    });
+
+    it("Removes items", async () => {
+      await program.methods
+        .removeChecklistItem(0, null)
+        .accounts(manageAccounts())
+        .signers([user.payer])
+        .rpc();
+      const checklist = await program.account.checklist.fetch(checklistPda);
+      expect(checklist.items.map((item) => item.text)).to.deep.equal(["Write draft", "Peer review"]);
+    });
+
+    it("Auto-completes the task when every item is checked", async () => {
+      await program.methods
+        .setChecklistAutoComplete(true, null)
+        .accounts(manageAccounts())
+        .signers([user.payer])
+        .rpc();
+
+      await toggle(0);
+      let task = await program.account.taskAccount.fetch(checklistTaskPda);
+      expect(task.status).to.deep.equal({ todo: {} });
+
+      await toggle(1);
+      expect(await completion()).to.equal(100);
+      task = await program.account.taskAccount.fetch(checklistTaskPda);
+      expect(task.status).to.deep.equal({ done: {} });
+      expect(task.completedAt).to.not.be.null;
+    });
+
+    it("Refuses checklist auto-completion through an edit-only session key", async () => {
+      const SESSION_EDIT = 1 << 2;
+      const editKey = anchor.web3.Keypair.generate();
+      const [editSessionPda] = findSessionPda(user.publicKey, editKey.publicKey);
+      await program.methods
+        .createSessionKey(editKey.publicKey, new BN(Math.floor(Date.now() / 1000) + 3600), SESSION_EDIT)
+        .accounts({
+          session: editSessionPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+      const taskPda = await createTask("Session Checklist Task");
+      const [taskChecklistPda] = findChecklistPda(taskPda);
+      const sessionAccounts = {
+        taskAccount: taskPda,
+        taskChecklist: taskChecklistPda,
+        sessionKey: editSessionPda,
+        authority: editKey.publicKey,
+      };
+      await program.methods
+        .addChecklistItem("Only item", null)
+        .accounts({
+          taskAccount: taskPda,
+          taskChecklist: taskChecklistPda,
+          taskAuthority: user.publicKey,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+      try {
+        await program.methods
+          .setChecklistAutoComplete(true, null)
+          .accounts(sessionAccounts)
+          .signers([editKey])
+          .rpc();
+        expect.fail("Should have failed because the session cannot change status");
+      } catch (error) {
+        expect(error.toString()).to.include("SessionPermissionDenied");
+      }
+
+      await program.methods
+        .setChecklistAutoComplete(true, null)
+        .accounts({ taskAccount: taskPda, taskChecklist: taskChecklistPda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      try {
+        await program.methods
+          .toggleChecklistItem(0, null)
+          .accounts({ ...sessionAccounts, userProfile: profilePda })
+          .signers([editKey])
+          .rpc();
+        expect.fail("Should have failed because the toggle would complete the task");
+      } catch (error) {
+        expect(error.toString()).to.include("SessionPermissionDenied");
+      }
+      const task = await program.account.taskAccount.fetch(taskPda);
+      expect(task.status).to.deep.equal({ todo: {} });
+    });
+
+    it("Closes the checklist with its task", async () => {
+      await program.methods
+        .deleteTask()
+        .accounts({
+          taskAccount: checklistTaskPda,
+          userProfile: profilePda,
+          taskDescription: findDescriptionPda(checklistTaskPda)[0],
+          taskChecklist: checklistPda,
+          authority: user.publicKey,
+          authoritySigner: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(checklistPda)).to.be.null;
+    });
+  });
+
+  describe("projects", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const [projectPda] = findProjectPda(user.publicKey, "Roadmap");
+    let projectTaskPda: anchor.web3.PublicKey;
+
+    const setActive = (active: boolean, project: anchor.web3.PublicKey | null) =>
+      program.methods
+        .updateTaskStatus(active, null)
+        .accounts({ taskAccount: projectTaskPda, userProfile: profilePda, project, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+
+    const closeProject = () =>
+      program.methods
+        .closeProject()
+        .accounts({ project: projectPda, owner: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .createProject("Roadmap")
+        .accounts({ project: projectPda, owner: user.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .signers([user.payer])
+        .rpc();
+    });
+
+    it("Allocates task IDs from the project counter", async () => {
+      const profileBefore = await program.account.userProfile.fetch(profilePda);
+      projectTaskPda = await createTask("Project Task", { project: projectPda });
+
+      const task = await program.account.taskAccount.fetch(projectTaskPda);
+      expect(task.id.toNumber()).to.equal(0);
+      expect(task.project.equals(projectPda)).to.be.true;
+
+      const project = await program.account.project.fetch(projectPda);
+      expect(project.name).to.equal("Roadmap");
+      expect(project.nextTaskId.toNumber()).to.equal(1);
+      expect(project.openTaskCount.toNumber()).to.equal(1);
+
+      const profile = await program.account.userProfile.fetch(profilePda);
+      expect(profile.nextTaskId.eq(profileBefore.nextTaskId)).to.be.true;
+      expect(profile.activeTaskCount.toNumber()).to.equal(profileBefore.activeTaskCount.toNumber() + 1);
+    });
+
+    it("Refuses neighbours from outside the project", async () => {
+      const personalPda = await createTask("Personal Neighbour");
+
+      try {
+        await program.methods
+          .reorderTask(null)
+          .accounts({
+            taskAccount: projectTaskPda,
+            prevTask: personalPda,
+            nextTask: null,
+            userProfile: profilePda,
+            project: projectPda,
+            authority: user.publicKey,
+          })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to a neighbour outside the project");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNeighbours");
+      }
+    });
+
+    it("Tags project tasks from the project's registry", async () => {
+      const [projectRegistryPda] = findTagRegistryPda(projectPda);
+      await program.methods
+        .createTag("milestone")
+        .accounts({
+          tagRegistry: projectRegistryPda,
+          project: projectPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+      const registry = await program.account.tagRegistry.fetch(projectRegistryPda);
+      expect(registry.namespace.equals(projectPda)).to.be.true;
+
+      const tagProjectTask = (tagRegistry: anchor.web3.PublicKey) =>
+        program.methods
+          .addTaskTag(0, null)
+          .accounts({ taskAccount: projectTaskPda, tagRegistry, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+      try {
+        await tagProjectTask(findTagRegistryPda(user.publicKey)[0]);
+        expect.fail("Should have failed because the task draws tags from its project");
+      } catch (error) {
+        expect(error.toString()).to.include("ConstraintSeeds");
+      }
+      await tagProjectTask(projectRegistryPda);
+      expect((await program.account.taskAccount.fetch(projectTaskPda)).tags).to.equal(1);
+    });
+
+    it("Refuses to close a project with open tasks", async () => {
+      try {
+        await closeProject();
+        expect.fail("Should have failed due to open tasks");
+      } catch (error) {
+        expect(error.toString()).to.include("ProjectHasOpenTasks");
+      }
+    });
+
+    it("Keeps the project counts in step with task status", async () => {
+      try {
+        await setActive(false, null);
+        expect.fail("Should have failed without the project account");
+      } catch (error) {
+        expect(error.toString()).to.include("ProjectMismatch");
+      }
+
+      await setActive(false, projectPda);
+      const project = await program.account.project.fetch(projectPda);
+      expect(project.openTaskCount.toNumber()).to.equal(0);
+      expect(project.closedTaskCount.toNumber()).to.equal(1);
+    });
+
+    it("Closes the project once its tasks are closed", async () => {
+      await closeProject();
+      const project = await program.account.project.fetch(projectPda);
+      expect(project.closed).to.be.true;
+
+      try {
+        await createTask("Too Late", { project: projectPda });
+        expect.fail("Should have failed because the project is closed");
+      } catch (error) {
+        expect(error.toString()).to.include("ProjectClosed");
+      }
+      try {
+        await setActive(true, projectPda);
+        expect.fail("Should have failed because the project is closed");
+      } catch (error) {
+        expect(error.toString()).to.include("ProjectClosed");
+      }
+    });
+
+    it("Removes deleted tasks from the project counts", async () => {
+      await program.methods
+        .deleteTask()
+        .accounts({
+          taskAccount: projectTaskPda,
+          userProfile: profilePda,
+          taskDescription: findDescriptionPda(projectTaskPda)[0],
+          taskChecklist: findChecklistPda(projectTaskPda)[0],
+          bountyVault: findBountyPda(projectTaskPda)[0],
+          project: projectPda,
+          authority: user.publicKey,
+          authoritySigner: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      const project = await program.account.project.fetch(projectPda);
+      expect(project.closedTaskCount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("workspaces", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const [workspacePda] = findWorkspacePda(user.publicKey);
+    const editor = anchor.web3.Keypair.generate();
+    const admin = anchor.web3.Keypair.generate();
+    let teamTaskPda: anchor.web3.PublicKey;
+
+    const invite = (member: anchor.web3.PublicKey, role: object, actor: anchor.web3.Keypair) =>
+      program.methods
+        .inviteMember(member, role as any)
+        .accounts({
+          workspace: workspacePda,
+          actorMember: findMemberPda(workspacePda, actor.publicKey)[0],
+          member: findMemberPda(workspacePda, member)[0],
+          actor: actor.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([actor])
+        .rpc();
+
+    const accept = (member: anchor.web3.Keypair) =>
+      program.methods
+        .acceptInvitation()
+        .accounts({ member: findMemberPda(workspacePda, member.publicKey)[0], user: member.publicKey })
+        .signers([member])
+        .rpc();
+
+    const setActiveAs = (member: anchor.web3.Keypair, active: boolean) =>
+      program.methods
+        .updateTaskStatus(active, null)
+        .accounts({
+          taskAccount: teamTaskPda,
+          userProfile: profilePda,
+          member: findMemberPda(workspacePda, member.publicKey)[0],
+          authority: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+
+    const deleteAs = (member: anchor.web3.Keypair) =>
+      program.methods
+        .deleteTask()
+        .accounts({
+          taskAccount: teamTaskPda,
+          userProfile: profilePda,
+          taskDescription: findDescriptionPda(teamTaskPda)[0],
+          taskChecklist: findChecklistPda(teamTaskPda)[0],
+          bountyVault: findBountyPda(teamTaskPda)[0],
+          member: findMemberPda(workspacePda, member.publicKey)[0],
+          authority: user.publicKey,
+          authoritySigner: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+
+    before(async () => {
+      for (const member of [editor, admin]) {
+        await provider.connection.requestAirdrop(member.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      }
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      await program.methods
+        .createWorkspace("Team")
+        .accounts({
+          workspace: workspacePda,
+          ownerMember: findMemberPda(workspacePda, user.publicKey)[0],
+          owner: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+      const pda = await createTask("Team Task");
+      teamTaskPda = pda;
+    });
+
+    it("Creates a workspace with the owner as its first member", async () => {
+      const workspace = await program.account.workspace.fetch(workspacePda);
+      expect(workspace.owner.equals(user.publicKey)).to.be.true;
+      expect(workspace.memberCount).to.equal(1);
+      const owner = await program.account.member.fetch(findMemberPda(workspacePda, user.publicKey)[0]);
+      expect(owner.role).to.deep.equal({ owner: {} });
+      expect(owner.accepted).to.be.true;
+    });
+
+    it("Lets an editor change task status once the invitation is accepted", async () => {
+      await invite(editor.publicKey, { editor: {} }, user.payer);
+      try {
+        await setActiveAs(editor, false);
+        expect.fail("Should have failed before the invitation was accepted");
+      } catch (error) {
+        expect(error.toString()).to.include("InvitationPending");
+      }
+
+      await accept(editor);
+      await setActiveAs(editor, false);
+      const task = await program.account.taskAccount.fetch(teamTaskPda);
+      expect(task.status).to.deep.equal({ done: {} });
+    });
+
+    it("Refuses to let an editor delete a task", async () => {
+      try {
+        await deleteAs(editor);
+        expect.fail("Should have failed due to the editor role");
+      } catch (error) {
+        expect(error.toString()).to.include("EditorUnauthorized");
+      }
+    });
+
+    it("Refuses to let an editor archive a task", async () => {
+      try {
+        await program.methods
+          .transitionTask({ archived: {} } as any, null)
+          .accounts({
+            taskAccount: teamTaskPda,
+            userProfile: profilePda,
+            member: findMemberPda(workspacePda, editor.publicKey)[0],
+            authority: editor.publicKey,
+          })
+          .signers([editor])
+          .rpc();
+        expect.fail("Should have failed due to the editor role");
+      } catch (error) {
+        expect(error.toString()).to.include("EditorUnauthorized");
+      }
+    });
+
+    it("Refuses to let a viewer change task status", async () => {
+      await program.methods
+        .changeMemberRole({ viewer: {} } as any)
+        .accounts({
+          workspace: workspacePda,
+          actorMember: findMemberPda(workspacePda, user.publicKey)[0],
+          member: findMemberPda(workspacePda, editor.publicKey)[0],
+          actor: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      try {
+        await setActiveAs(editor, true);
+        expect.fail("Should have failed due to the viewer role");
+      } catch (error) {
+        expect(error.toString()).to.include("ViewerUnauthorized");
+      }
+    });
+
+    it("Limits admins to managing editors and viewers", async () => {
+      await invite(admin.publicKey, { admin: {} }, user.payer);
+      await accept(admin);
+      try {
+        await invite(anchor.web3.Keypair.generate().publicKey, { admin: {} }, admin);
+        expect.fail("Should have failed due to the admin role");
+      } catch (error) {
+        expect(error.toString()).to.include("AdminUnauthorized");
+      }
+    });
+
+    it("Lets an admin delete a task, refunding the task authority", async () => {
+      const balanceBefore = await provider.connection.getBalance(user.publicKey);
+      await deleteAs(admin);
+      expect(await provider.connection.getAccountInfo(teamTaskPda)).to.be.null;
+      expect(await provider.connection.getBalance(user.publicKey)).to.be.greaterThan(balanceBefore);
+    });
+
+    it("Lets members leave on their own", async () => {
+      const [memberPda] = findMemberPda(workspacePda, editor.publicKey);
+      await program.methods
+        .removeMember()
+        .accounts({ workspace: workspacePda, actorMember: null, member: memberPda, actor: editor.publicKey })
+        .signers([editor])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(memberPda)).to.be.null;
+      const workspace = await program.account.workspace.fetch(workspacePda);
+      expect(workspace.memberCount).to.equal(2);
+    });
+  });
+
+  describe("assignees", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const assignee = anchor.web3.Keypair.generate();
+    const coAssignees = [1, 2, 3].map(() => anchor.web3.Keypair.generate().publicKey);
+    let assignedTaskPda: anchor.web3.PublicKey;
+
+    const assign = (member: anchor.web3.PublicKey, signer: anchor.web3.Keypair = user.payer) =>
+      program.methods
+        .assignTask(member, null)
+        .accounts({ taskAccount: assignedTaskPda, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await provider.connection.requestAirdrop(assignee.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const pda = await createTask("Assigned Task");
+      assignedTaskPda = pda;
+    });
+
+    it("Assigns a task and emits an event", async () => {
+      let event = null;
+      const listener = program.addEventListener("taskAssigned", (e) => {
+        event = e;
+      });
+      await assign(assignee.publicKey);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      const task = await program.account.taskAccount.fetch(assignedTaskPda);
+      expect(task.assignee.equals(assignee.publicKey)).to.be.true;
+      expect(event.task.equals(assignedTaskPda)).to.be.true;
+      expect(event.assignee.equals(assignee.publicKey)).to.be.true;
+      expect(event.assignedBy.equals(user.publicKey)).to.be.true;
+
+      try {
+        await assign(assignee.publicKey);
+        expect.fail("Should have failed because the user is already assigned");
+      } catch (error) {
+        expect(error.toString()).to.include("AlreadyAssigned");
+      }
+    });
+
+    it("Caps the number of co-assignees", async () => {
+      for (const coAssignee of coAssignees) {
+        await assign(coAssignee);
+      }
+      const task = await program.account.taskAccount.fetch(assignedTaskPda);
+      expect(task.coAssignees.map((key) => key.toBase58())).to.deep.equal(coAssignees.map((key) => key.toBase58()));
+
+      try {
+        await assign(anchor.web3.Keypair.generate().publicKey);
+        expect.fail("Should have failed because the task has no room for more assignees");
+      } catch (error) {
+        expect(error.toString()).to.include("AssigneesFull");
+      }
+    });
+
+    it("Only lets the creator or an admin assign", async () => {
+      try {
+        await assign(anchor.web3.Keypair.generate().publicKey, assignee);
+        expect.fail("Should have failed due to unauthorized action");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+    });
+
+    it("Lets the assignee change status but not delete", async () => {
+      await program.methods
+        .updateTaskStatus(false, null)
+        .accounts({ taskAccount: assignedTaskPda, userProfile: profilePda, authority: assignee.publicKey })
+        .signers([assignee])
+        .rpc();
+      const task = await program.account.taskAccount.fetch(assignedTaskPda);
+      expect(task.status).to.deep.equal({ done: {} });
+
+      try {
+        await program.methods
+          .deleteTask()
+          .accounts({
+            taskAccount: assignedTaskPda,
+            userProfile: profilePda,
+            taskDescription: findDescriptionPda(assignedTaskPda)[0],
+            taskChecklist: findChecklistPda(assignedTaskPda)[0],
+            bountyVault: findBountyPda(assignedTaskPda)[0],
+            authority: user.publicKey,
+            authoritySigner: assignee.publicKey,
+          })
+          .signers([assignee])
+          .rpc();
+        expect.fail("Should have failed due to unauthorized action");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+    });
+
+    it("Promotes a co-assignee when the assignee steps down", async () => {
+      await program.methods
+        .unassignTask(assignee.publicKey, null)
+        .accounts({ taskAccount: assignedTaskPda, authority: assignee.publicKey })
+        .signers([assignee])
+        .rpc();
+      const task = await program.account.taskAccount.fetch(assignedTaskPda);
+      expect(task.assignee.equals(coAssignees[0])).to.be.true;
+      expect(task.coAssignees.length).to.equal(coAssignees.length - 1);
+    });
+  });
+
+  describe("ownership transfer", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const newOwner = anchor.web3.Keypair.generate();
+    const [newOwnerProfilePda] = findProfilePda(newOwner.publicKey);
+    let transferredTaskPda: anchor.web3.PublicKey;
+
+    const propose = (expiresAtSlot: BN | null) =>
+      program.methods
+        .proposeTransfer(newOwner.publicKey, expiresAtSlot)
+        .accounts({ taskAccount: transferredTaskPda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+
+    const accept = (signer: anchor.web3.Keypair) =>
+      program.methods
+        .acceptTransfer()
+        .accounts({
+          taskAccount: transferredTaskPda,
+          previousOwnerProfile: profilePda,
+          newOwnerProfile: findProfilePda(signer.publicKey)[0],
+          newOwner: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    const deleteAccounts = (owner: anchor.web3.PublicKey) => ({
+      taskAccount: transferredTaskPda,
+      userProfile: findProfilePda(owner)[0],
+      taskDescription: findDescriptionPda(transferredTaskPda)[0],
+      taskChecklist: findChecklistPda(transferredTaskPda)[0],
+      bountyVault: findBountyPda(transferredTaskPda)[0],
+      authority: owner,
+      authoritySigner: owner,
+    });
+
+    before(async () => {
+      await provider.connection.requestAirdrop(newOwner.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const pda = await createTask("Transferred Task");
+      transferredTaskPda = pda;
+      await program.methods
+        .addTaskTag(0, null)
+        .accounts({
+          taskAccount: pda,
+          tagRegistry: findTagRegistryPda(user.publicKey)[0],
+          authority: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+    });
+
+    it("Rejects an offer that has already expired", async () => {
+      try {
+        await propose(new BN(1));
+        expect.fail("Should have failed because the expiry slot has passed");
+      } catch (error) {
+        expect(error.toString()).to.include("TransferExpired");
+      }
+    });
+
+    it("Proposes and cancels a transfer", async () => {
+      const slot = await provider.connection.getSlot();
+      await propose(new BN(slot + 1000));
+      let task = await program.account.taskAccount.fetch(transferredTaskPda);
+      expect(task.pendingOwner.equals(newOwner.publicKey)).to.be.true;
+      expect(task.transferExpiresAtSlot.toNumber()).to.equal(slot + 1000);
+
+      const cancel = () =>
+        program.methods
+          .cancelTransfer()
+          .accounts({ taskAccount: transferredTaskPda, signer: newOwner.publicKey })
+          .signers([newOwner])
+          .rpc();
+      await cancel();
+      task = await program.account.taskAccount.fetch(transferredTaskPda);
+      expect(task.pendingOwner).to.be.null;
+
+      try {
+        await cancel();
+        expect.fail("Should have failed because no transfer is pending");
+      } catch (error) {
+        expect(error.toString()).to.include("NoPendingTransfer");
+      }
+    });
+
+    it("Only lets the pending owner accept", async () => {
+      await propose(null);
+      const stranger = anchor.web3.Keypair.generate();
+      await provider.connection.requestAirdrop(stranger.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      try {
+        await accept(stranger);
+        expect.fail("Should have failed due to unauthorized action");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+    });
+
+    it("Transfers the task on acceptance", async () => {
+      const previousProfileBefore = await program.account.userProfile.fetch(profilePda);
+      await accept(newOwner);
+
+      const task = await program.account.taskAccount.fetch(transferredTaskPda);
+      expect(task.authority.equals(newOwner.publicKey)).to.be.true;
+      expect(task.creator.equals(user.publicKey)).to.be.true;
+      expect(task.pendingOwner).to.be.null;
+      // Tags keep pointing into the creator's registry.
+      expect(task.tags).to.equal(1);
+
+      const previousProfile = await program.account.userProfile.fetch(profilePda);
+      expect(previousProfile.activeTaskCount.toNumber()).to.equal(previousProfileBefore.activeTaskCount.toNumber() - 1);
+      const newOwnerProfile = await program.account.userProfile.fetch(newOwnerProfilePda);
+      expect(newOwnerProfile.activeTaskCount.toNumber()).to.equal(1);
+    });
+
+    it("Refunds the new owner when the task is deleted", async () => {
+      try {
+        await program.methods.deleteTask().accounts(deleteAccounts(user.publicKey)).signers([user.payer]).rpc();
+        expect.fail("Should have failed because the task was transferred");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+
+      const taskRent = await provider.connection.getBalance(transferredTaskPda);
+      const balanceBefore = await provider.connection.getBalance(newOwner.publicKey);
+      await program.methods
+        .deleteTask()
+        .accounts(deleteAccounts(newOwner.publicKey))
+        .signers([newOwner])
+        .rpc();
+      const balanceAfter = await provider.connection.getBalance(newOwner.publicKey);
+      expect(balanceAfter).to.be.greaterThan(balanceBefore + taskRent / 2);
+    });
+  });
+
+  describe("session keys", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const hotKey = anchor.web3.Keypair.generate();
+    const [sessionPda] = findSessionPda(user.publicKey, hotKey.publicKey);
+    const SESSION_UPDATE_STATUS = 1 << 0;
+    const SESSION_RENAME = 1 << 1;
+    let sessionTaskPda: anchor.web3.PublicKey;
+
+    const createSession = (expiresAt: number, permissions: number) =>
+      program.methods
+        .createSessionKey(hotKey.publicKey, new BN(expiresAt), permissions)
+        .accounts({
+          session: sessionPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const setActiveWithSession = (active: boolean) =>
+      program.methods
+        .updateTaskStatus(active, null)
+        .accounts({
+          taskAccount: sessionTaskPda,
+          userProfile: profilePda,
+          sessionKey: sessionPda,
+          authority: hotKey.publicKey,
+        })
+        .signers([hotKey])
+        .rpc();
+
+    before(async () => {
+      await provider.connection.requestAirdrop(hotKey.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+
+      const pda = await createTask("Session Task");
+      sessionTaskPda = pda;
+    });
+
+    it("Rejects invalid session keys", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      try {
+        await createSession(now - 60, SESSION_UPDATE_STATUS);
+        expect.fail("Should have failed because the expiry has passed");
+      } catch (error) {
+        expect(error.toString()).to.include("SessionExpired");
+      }
+      try {
+        await createSession(now + 3600, 0);
+        expect.fail("Should have failed due to empty permissions");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidSessionPermissions");
+      }
+    });
+
+    it("Lets a session key update status and rename", async () => {
+      await createSession(Math.floor(Date.now() / 1000) + 3600, SESSION_UPDATE_STATUS | SESSION_RENAME);
+      const session = await program.account.sessionKey.fetch(sessionPda);
+      expect(session.authority.equals(user.publicKey)).to.be.true;
+      expect(session.permissions).to.equal(SESSION_UPDATE_STATUS | SESSION_RENAME);
+
+      await setActiveWithSession(false);
+      // A shorter name refunds rent to the task authority, never to the session key.
+      const hotKeyBalance = await provider.connection.getBalance(hotKey.publicKey);
+      await program.methods
+        .updateTask({ name: "By Session", expectedRevision: null })
+        .accounts({
+          taskAccount: sessionTaskPda,
+          taskAuthority: user.publicKey,
+          sessionKey: sessionPda,
+          authority: hotKey.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([hotKey])
+        .rpc();
+
+      const task = await program.account.taskAccount.fetch(sessionTaskPda);
+      expect(task.status).to.deep.equal({ done: {} });
+      expect(task.name).to.equal("By Session");
+      expect(await provider.connection.getBalance(hotKey.publicKey)).to.equal(hotKeyBalance);
+    });
+
+    it("Refuses actions outside the session's permissions", async () => {
+      try {
+        await program.methods
+          .setTaskPriority({ high: {} } as any, null)
+          .accounts({ taskAccount: sessionTaskPda, sessionKey: sessionPda, authority: hotKey.publicKey })
+          .signers([hotKey])
+          .rpc();
+        expect.fail("Should have failed because the session cannot edit");
+      } catch (error) {
+        expect(error.toString()).to.include("SessionPermissionDenied");
+      }
+      try {
+        await program.methods
+          .deleteTask()
+          .accounts({
+            taskAccount: sessionTaskPda,
+            userProfile: profilePda,
+            taskDescription: findDescriptionPda(sessionTaskPda)[0],
+            taskChecklist: findChecklistPda(sessionTaskPda)[0],
+            bountyVault: findBountyPda(sessionTaskPda)[0],
+            authority: user.publicKey,
+            authoritySigner: hotKey.publicKey,
+          })
+          .signers([hotKey])
+          .rpc();
+        expect.fail("Should have failed because sessions cannot delete");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+    });
+
+    it("Stops accepting a revoked session key", async () => {
+      await program.methods
+        .revokeSessionKey()
+        .accounts({ session: sessionPda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(sessionPda)).to.be.null;
+
+      try {
+        await setActiveWithSession(true);
+        expect.fail("Should have failed because the session was revoked");
+      } catch (error) {
+        expect(error).to.be.an("error");
+      }
+    });
+  });
+
+  describe("approval policies", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const approverA = anchor.web3.Keypair.generate();
+    const approverB = anchor.web3.Keypair.generate();
+    let guardedTaskPda: anchor.web3.PublicKey;
+    let policyPda: anchor.web3.PublicKey;
+    let pendingPda: anchor.web3.PublicKey;
+
+    const deleteGuarded = (pendingAction: anchor.web3.PublicKey | null) =>
+      program.methods
+        .deleteTask()
+        .accounts({
+          taskAccount: guardedTaskPda,
+          userProfile: profilePda,
+          taskDescription: findDescriptionPda(guardedTaskPda)[0],
+          taskChecklist: findChecklistPda(guardedTaskPda)[0],
+          bountyVault: findBountyPda(guardedTaskPda)[0],
+          pendingAction,
+          proposer: pendingAction && user.publicKey,
+          authority: user.publicKey,
+          authoritySigner: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const approve = (approver: anchor.web3.Keypair) =>
+      program.methods
+        .approveAction()
+        .accounts({ pendingAction: pendingPda, approvalPolicy: policyPda, approver: approver.publicKey })
+        .signers([approver])
+        .rpc();
+
+    before(async () => {
+      const pda = await createTask("Guarded Task");
+      guardedTaskPda = pda;
+      [policyPda] = findApprovalPolicyPda(pda);
+      [pendingPda] = findPendingActionPda(pda);
+    });
+
+    it("Rejects invalid policies", async () => {
+      try {
+        await program.methods
+          .createTaskApprovalPolicy([approverA.publicKey, approverA.publicKey], 1)
+          .accounts({
+            taskAccount: guardedTaskPda,
+            approvalPolicy: policyPda,
+            authority: user.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed due to duplicate approvers");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidApprovalPolicy");
+      }
+    });
+
+    it("Requires a threshold of approvals to delete a guarded task", async () => {
+      await program.methods
+        .createTaskApprovalPolicy([approverA.publicKey, approverB.publicKey], 2)
+        .accounts({
+          taskAccount: guardedTaskPda,
+          approvalPolicy: policyPda,
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+      const task = await program.account.taskAccount.fetch(guardedTaskPda);
+      expect(task.approvalPolicy.equals(policyPda)).to.be.true;
+
+      try {
+        await deleteGuarded(null);
+        expect.fail("Should have failed without an approved pending action");
+      } catch (error) {
+        expect(error.toString()).to.include("ApprovalRequired");
+      }
+
+      await program.methods
+        .proposeAction({ delete: {} }, new BN(Math.floor(Date.now() / 1000) + 3600))
+        .accounts({
+          taskAccount: guardedTaskPda,
+          approvalPolicy: policyPda,
+          pendingAction: pendingPda,
+          proposer: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+      await approve(approverA);
+      try {
+        await approve(approverA);
+        expect.fail("Should have failed because the approver already approved");
+      } catch (error) {
+        expect(error.toString()).to.include("AlreadyApproved");
+      }
+      try {
+        await deleteGuarded(pendingPda);
+        expect.fail("Should have failed below the threshold");
+      } catch (error) {
+        expect(error.toString()).to.include("ApprovalRequired");
+      }
+
+      await approve(approverB);
+      const pending = await program.account.pendingAction.fetch(pendingPda);
+      expect(pending.approvals).to.equal(0b11);
+
+      await deleteGuarded(pendingPda);
+      expect(await provider.connection.getAccountInfo(guardedTaskPda)).to.be.null;
+      expect(await provider.connection.getAccountInfo(pendingPda)).to.be.null;
+    });
+
+    describe("with a threshold of one", () => {
+      const proposer = anchor.web3.Keypair.generate();
+      const newOwner = anchor.web3.Keypair.generate();
+
+      const guard = (task: anchor.web3.PublicKey) =>
+        program.methods
+          .createTaskApprovalPolicy([approverA.publicKey, proposer.publicKey], 1)
+          .accounts({
+            taskAccount: task,
+            approvalPolicy: findApprovalPolicyPda(task)[0],
+            authority: user.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([user.payer])
+          .rpc();
+
+      const propose = (task: anchor.web3.PublicKey, action: object, expiresAt: number) =>
+        program.methods
+          .proposeAction(action as any, new BN(expiresAt))
+          .accounts({
+            taskAccount: task,
+            approvalPolicy: findApprovalPolicyPda(task)[0],
+            pendingAction: findPendingActionPda(task)[0],
+            proposer: proposer.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([proposer])
+          .rpc();
+
+      const approveFor = (task: anchor.web3.PublicKey) =>
+        program.methods
+          .approveAction()
+          .accounts({
+            pendingAction: findPendingActionPda(task)[0],
+            approvalPolicy: findApprovalPolicyPda(task)[0],
+            approver: approverA.publicKey,
+          })
+          .signers([approverA])
+          .rpc();
+
+      const cancel = (task: anchor.web3.PublicKey, signer: anchor.web3.Keypair) =>
+        program.methods
+          .cancelAction()
+          .accounts({
+            taskAccount: task,
+            pendingAction: findPendingActionPda(task)[0],
+            proposer: proposer.publicKey,
+            signer: signer.publicKey,
+          })
+          .signers([signer])
+          .rpc();
+
+      const inAnHour = () => Math.floor(Date.now() / 1000) + 3600;
+
+      before(async () => {
+        await provider.connection.requestAirdrop(proposer.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+      });
+
+      it("Refunds the proposer when a pending action is cancelled", async () => {
+        const task = await createTask("Cancelled Action Task");
+        await guard(task);
+        await propose(task, { delete: {} }, inAnHour());
+
+        const stranger = anchor.web3.Keypair.generate();
+        try {
+          await cancel(task, stranger);
+          expect.fail("Should have failed due to unauthorized action");
+        } catch (error) {
+          expect(error.toString()).to.include("UnauthorizedAction");
+        }
+
+        const pendingRent = await provider.connection.getBalance(findPendingActionPda(task)[0]);
+        const balanceBefore = await provider.connection.getBalance(proposer.publicKey);
+        await cancel(task, user.payer);
+        expect(await provider.connection.getAccountInfo(findPendingActionPda(task)[0])).to.be.null;
+        expect(await provider.connection.getBalance(proposer.publicKey)).to.equal(balanceBefore + pendingRent);
+      });
+
+      it("Refuses approvals once a pending action has expired", async () => {
+        const task = await createTask("Expired Action Task");
+        await guard(task);
+        const slot = await provider.connection.getSlot();
+        await propose(task, { delete: {} }, (await provider.connection.getBlockTime(slot)) + 2);
+        await new Promise((resolve) => setTimeout(resolve, 4000));
+
+        try {
+          await approveFor(task);
+          expect.fail("Should have failed because the action expired");
+        } catch (error) {
+          expect(error.toString()).to.include("ActionExpired");
+        }
+
+        // Anyone may clear an expired action.
+        await cancel(task, approverA);
+        expect(await provider.connection.getAccountInfo(findPendingActionPda(task)[0])).to.be.null;
+      });
+
+      it("Requires approval to archive a guarded task", async () => {
+        const task = await createTask("Archived Guarded Task");
+        await guard(task);
+        const transition = (
+          status: object,
+          pendingAction: anchor.web3.PublicKey | null,
+          actionProposer: anchor.web3.PublicKey | null = pendingAction && proposer.publicKey
+        ) =>
+          program.methods
+            .transitionTask(status as any, null)
+            .accounts({
+              taskAccount: task,
+              userProfile: profilePda,
+              pendingAction,
+              proposer: actionProposer,
+              authority: user.publicKey,
+            })
+            .signers([user.payer])
+            .rpc();
+
+        try {
+          await transition({ archived: {} }, null);
+          expect.fail("Should have failed without an approved pending action");
+        } catch (error) {
+          expect(error.toString()).to.include("ApprovalRequired");
+        }
+
+        await propose(task, { delete: {} }, inAnHour());
+        await approveFor(task);
+        try {
+          await transition({ archived: {} }, findPendingActionPda(task)[0]);
+          expect.fail("Should have failed because the approval is for a different action");
+        } catch (error) {
+          expect(error.toString()).to.include("PendingActionMismatch");
+        }
+        await cancel(task, proposer);
+
+        await propose(task, { archive: {} }, inAnHour());
+        await approveFor(task);
+        try {
+          await transition({ archived: {} }, findPendingActionPda(task)[0], user.publicKey);
+          expect.fail("Should have failed because the rent must go back to the proposer");
+        } catch (error) {
+          expect(error.toString()).to.include("UnauthorizedAction");
+        }
+
+        const pendingRent = await provider.connection.getBalance(findPendingActionPda(task)[0]);
+        const balanceBefore = await provider.connection.getBalance(proposer.publicKey);
+        await transition({ archived: {} }, findPendingActionPda(task)[0]);
+        expect((await program.account.taskAccount.fetch(task)).status).to.deep.equal({ archived: {} });
+        expect(await provider.connection.getAccountInfo(findPendingActionPda(task)[0])).to.be.null;
+        expect(await provider.connection.getBalance(proposer.publicKey)).to.equal(balanceBefore + pendingRent);
+      });
+
+      it("Withdraws an open transfer offer and then requires approval to transfer", async () => {
+        const task = await createTask("Transferred Guarded Task");
+        const proposeTransfer = (pendingAction: anchor.web3.PublicKey | null) =>
+          program.methods
+            .proposeTransfer(newOwner.publicKey, null)
+            .accounts({
+              taskAccount: task,
+              pendingAction,
+              proposer: pendingAction && proposer.publicKey,
+              authority: user.publicKey,
+            })
+            .signers([user.payer])
+            .rpc();
+
+        // An offer made before the policy cannot slip past it.
+        await proposeTransfer(null);
+        await guard(task);
+        expect((await program.account.taskAccount.fetch(task)).pendingOwner).to.be.null;
+
+        try {
+          await proposeTransfer(null);
+          expect.fail("Should have failed without an approved pending action");
+        } catch (error) {
+          expect(error.toString()).to.include("ApprovalRequired");
+        }
+
+        await propose(task, { transfer: { newOwner: newOwner.publicKey } }, inAnHour());
+        await approveFor(task);
+        await proposeTransfer(findPendingActionPda(task)[0]);
+        expect((await program.account.taskAccount.fetch(task)).pendingOwner.equals(newOwner.publicKey)).to.be.true;
+      });
+
+      it("Puts new project tasks under the project's policy", async () => {
+        const [projectPda] = findProjectPda(user.publicKey, "Guarded");
+        const [projectPolicyPda] = findApprovalPolicyPda(projectPda);
+        await program.methods
+          .createProject("Guarded")
+          .accounts({ project: projectPda, owner: user.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+          .signers([user.payer])
+          .rpc();
+        await program.methods
+          .createProjectApprovalPolicy([approverA.publicKey], 1)
+          .accounts({
+            project: projectPda,
+            approvalPolicy: projectPolicyPda,
+            owner: user.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([user.payer])
+          .rpc();
+        const policy = await program.account.approvalPolicy.fetch(projectPolicyPda);
+        expect(policy.scope.equals(projectPda)).to.be.true;
+
+        const taskPda = await createTask("Guarded Project Task", { project: projectPda });
+        const task = await program.account.taskAccount.fetch(taskPda);
+        expect(task.approvalPolicy.equals(projectPolicyPda)).to.be.true;
+      });
+    });
+  });
+
+  describe("bounties", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const hunter = anchor.web3.Keypair.generate();
+    const reward = anchor.web3.LAMPORTS_PER_SOL / 100;
+    let bountyTaskPda: anchor.web3.PublicKey;
+    let vaultPda: anchor.web3.PublicKey;
+
+    before(async () => {
+      const pda = await createTask("Bounty Task");
+      bountyTaskPda = pda;
+      [vaultPda] = findBountyPda(pda);
+      await program.methods
+        .assignTask(hunter.publicKey, null)
+        .accounts({ taskAccount: pda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      await program.methods
+        .fundBounty(new BN(reward), new BN(Math.floor(Date.now() / 1000) + 3600))
+        .accounts({
+          taskAccount: pda,
+          bountyVault: vaultPda,
+          funder: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+    });
+
+    it("Refuses to delete a task with a funded bounty", async () => {
+      const vault = await program.account.bountyVault.fetch(vaultPda);
+      expect(vault.amount.toNumber()).to.equal(reward);
+      try {
+        await program.methods
+          .deleteTask()
+          .accounts({
+            taskAccount: bountyTaskPda,
+            userProfile: profilePda,
+            taskDescription: findDescriptionPda(bountyTaskPda)[0],
+            taskChecklist: findChecklistPda(bountyTaskPda)[0],
+            bountyVault: vaultPda,
+            authority: user.publicKey,
+            authoritySigner: user.publicKey,
+          })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed because the bounty is funded");
+      } catch (error) {
+        expect(error.toString()).to.include("BountyFunded");
+      }
+    });
+
+    it("Pays the assignee once the task is done", async () => {
+      const payAccounts = {
+        taskAccount: bountyTaskPda,
+        bountyVault: vaultPda,
+        assignee: hunter.publicKey,
+        funder: user.publicKey,
+        authority: user.publicKey,
+      };
+      try {
+        await program.methods.completeAndPay().accounts(payAccounts).signers([user.payer]).rpc();
+        expect.fail("Should have failed because the task is not done");
+      } catch (error) {
+        expect(error.toString()).to.include("TaskNotDone");
+      }
+
+      await program.methods
+        .updateTaskStatus(false, null)
+        .accounts({ taskAccount: bountyTaskPda, userProfile: profilePda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      await program.methods.completeAndPay().accounts(payAccounts).signers([user.payer]).rpc();
+
+      expect(await provider.connection.getBalance(hunter.publicKey)).to.equal(reward);
+      expect(await provider.connection.getAccountInfo(vaultPda)).to.be.null;
+    });
+
+    it("Refunds an expired bounty to its funder", async () => {
+      const funder = anchor.web3.Keypair.generate();
+      await provider.connection.requestAirdrop(funder.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      const pda = await createTask("Refunded Bounty Task");
+      const [refundVaultPda] = findBountyPda(pda);
+      const slot = await provider.connection.getSlot();
+      await program.methods
+        .fundBounty(new BN(reward), new BN((await provider.connection.getBlockTime(slot)) + 3))
+        .accounts({
+          taskAccount: pda,
+          bountyVault: refundVaultPda,
+          funder: funder.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([funder])
+        .rpc();
+
+      const refund = () =>
+        program.methods
+          .refundBounty()
+          .accounts({ bountyVault: refundVaultPda, funder: funder.publicKey })
+          .signers([funder])
+          .rpc();
+      try {
+        await refund();
+        expect.fail("Should have failed because the bounty has not expired");
+      } catch (error) {
+        expect(error.toString()).to.include("BountyNotExpired");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+      const vaultBalance = await provider.connection.getBalance(refundVaultPda);
+      const balanceBefore = await provider.connection.getBalance(funder.publicKey);
+      await refund();
+      expect(await provider.connection.getAccountInfo(refundVaultPda)).to.be.null;
+      expect(await provider.connection.getBalance(funder.publicKey)).to.equal(balanceBefore + vaultBalance);
+    });
+
+    it("Ignores lamports sent straight to an unfunded bounty address", async () => {
+      const pda = await createTask("Griefed Bounty Task");
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: user.publicKey,
+            toPubkey: findBountyPda(pda)[0],
+            lamports: anchor.web3.LAMPORTS_PER_SOL / 100,
+          })
+        )
+      );
+
+      await program.methods
+        .deleteTask()
+        .accounts({
+          taskAccount: pda,
+          userProfile: profilePda,
+          taskDescription: findDescriptionPda(pda)[0],
+          taskChecklist: findChecklistPda(pda)[0],
+          bountyVault: findBountyPda(pda)[0],
+          authority: user.publicKey,
+          authoritySigner: user.publicKey,
+        })
+        .signers([user.payer])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(pda)).to.be.null;
+    });
+
+    it("Limits who can pin a bounty on a task and lets the task authority reject it", async () => {
+      const funder = anchor.web3.Keypair.generate();
+      await provider.connection.requestAirdrop(funder.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      const pda = await createTask("Rejected Bounty Task");
+      const [rejectVaultPda] = findBountyPda(pda);
+      const fund = (expiresAt: number) =>
+        program.methods
+          .fundBounty(new BN(reward), new BN(expiresAt))
+          .accounts({
+            taskAccount: pda,
+            bountyVault: rejectVaultPda,
+            funder: funder.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([funder])
+          .rpc();
+      const now = Math.floor(Date.now() / 1000);
+
+      try {
+        await fund(now + 2 * 365 * 24 * 60 * 60);
+        expect.fail("Should have failed because the expiry is too far out");
+      } catch (error) {
+        expect(error.toString()).to.include("BountyExpiryTooFar");
+      }
+      await fund(now + 3600);
+
+      const reject = (authority: anchor.web3.Keypair) =>
+        program.methods
+          .rejectBounty()
+          .accounts({
+            taskAccount: pda,
+            bountyVault: rejectVaultPda,
+            funder: funder.publicKey,
+            authority: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+      try {
+        await reject(funder);
+        expect.fail("Should have failed because only the task authority may reject");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+      const vaultBalance = await provider.connection.getBalance(rejectVaultPda);
+      const balanceBefore = await provider.connection.getBalance(funder.publicKey);
+      await reject(user.payer);
+      expect(await provider.connection.getAccountInfo(rejectVaultPda)).to.be.null;
+      expect(await provider.connection.getBalance(funder.publicKey)).to.equal(balanceBefore + vaultBalance);
+
+      await program.methods
+        .transitionTask({ archived: {} } as any, null)
+        .accounts({ taskAccount: pda, userProfile: profilePda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      try {
+        await fund(now + 3600);
+        expect.fail("Should have failed because the task is archived");
+      } catch (error) {
+        expect(error.toString()).to.include("TaskArchived");
+      }
+    });
+  });
+
+  for (const [label, tokenProgram, feeBasisPoints] of [
+    ["Token", TOKEN_PROGRAM_ID, 0],
+    ["Token-2022", TOKEN_2022_PROGRAM_ID, 0],
+    ["Token-2022 with transfer fees", TOKEN_2022_PROGRAM_ID, 100],
+  ] as const) {
+    describe(`token bounties (${label})`, () => {
+      const [profilePda] = findProfilePda(user.publicKey);
+      const hunter = anchor.web3.Keypair.generate();
+      const reward = 1_000_000;
+      // What arrives after one transfer; the fee cap is set above any amount used here.
+      const afterFee = (amount: number) => amount - Math.ceil((amount * feeBasisPoints) / 10_000);
+      let mint: anchor.web3.PublicKey;
+      let funderTokenAccount: anchor.web3.PublicKey;
+      let hunterTokenAccount: anchor.web3.PublicKey;
+
+      const bountyAccounts = (task: anchor.web3.PublicKey) => {
+        const [tokenBounty] = findTokenBountyPda(task);
+        return {
+          tokenBounty,
+          mint,
+          vault: getAssociatedTokenAddressSync(mint, tokenBounty, true, tokenProgram),
+          tokenProgram,
+        };
+      };
+
+      const fund = (task: anchor.web3.PublicKey, expiresAt: number) =>
+        program.methods
+          .fundTokenBounty(new BN(reward), new BN(expiresAt))
+          .accounts({
+            taskAccount: task,
+            ...bountyAccounts(task),
+            funderTokenAccount,
+            funder: user.publicKey,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([user.payer])
+          .rpc();
+
+      const createTransferFeeMint = async () => {
+        const mintKeypair = anchor.web3.Keypair.generate();
+        const space = getMintLen([ExtensionType.TransferFeeConfig]);
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            anchor.web3.SystemProgram.createAccount({
+              fromPubkey: user.publicKey,
+              newAccountPubkey: mintKeypair.publicKey,
+              space,
+              lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+              programId: tokenProgram,
+            }),
+            createInitializeTransferFeeConfigInstruction(
+              mintKeypair.publicKey,
+              user.publicKey,
+              user.publicKey,
+              feeBasisPoints,
+              BigInt(10 * reward),
+              tokenProgram
+            ),
+            createInitializeMintInstruction(mintKeypair.publicKey, 6, user.publicKey, null, tokenProgram)
+          ),
+          [mintKeypair]
+        );
+        return mintKeypair.publicKey;
+      };
+
+      before(async () => {
+        mint =
+          feeBasisPoints > 0
+            ? await createTransferFeeMint()
+            : await createMint(
+                provider.connection,
+                user.payer,
+                user.publicKey,
+                null,
+                6,
+                undefined,
+                undefined,
+                tokenProgram
+              );
+        funderTokenAccount = await createAssociatedTokenAccount(
+          provider.connection,
+          user.payer,
+          mint,
+          user.publicKey,
+          undefined,
+          tokenProgram
+        );
+        hunterTokenAccount = await createAssociatedTokenAccount(
+          provider.connection,
+          user.payer,
+          mint,
+          hunter.publicKey,
+          undefined,
+          tokenProgram
+        );
+        await mintTo(
+          provider.connection,
+          user.payer,
+          mint,
+          funderTokenAccount,
+          user.publicKey,
+          10 * reward,
+          [],
+          undefined,
+          tokenProgram
+        );
+      });
+
+      it("Pays the assignee once the task is done", async () => {
+        const task = await createTask(`${label} Bounty Task`);
+        await program.methods
+          .assignTask(hunter.publicKey, null)
+          .accounts({ taskAccount: task, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+        await fund(task, Math.floor(Date.now() / 1000) + 3600);
+        const { vault } = bountyAccounts(task);
+        const escrowed = afterFee(reward);
+        expect(Number((await getAccount(provider.connection, vault, undefined, tokenProgram)).amount)).to.equal(
+          escrowed
+        );
+
+        const payAccounts = {
+          taskAccount: task,
+          ...bountyAccounts(task),
+          assigneeTokenAccount: hunterTokenAccount,
+          funder: user.publicKey,
+          authority: user.publicKey,
+        };
+        try {
+          await program.methods.completeAndPayToken().accounts(payAccounts).signers([user.payer]).rpc();
+          expect.fail("Should have failed because the task is not done");
+        } catch (error) {
+          expect(error.toString()).to.include("TaskNotDone");
+        }
+
+        await program.methods
+          .updateTaskStatus(false, null)
+          .accounts({ taskAccount: task, userProfile: profilePda, authority: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+        await program.methods.completeAndPayToken().accounts(payAccounts).signers([user.payer]).rpc();
+
+        const hunterAccount = await getAccount(provider.connection, hunterTokenAccount, undefined, tokenProgram);
+        expect(Number(hunterAccount.amount)).to.equal(afterFee(escrowed));
+        expect(await provider.connection.getAccountInfo(vault)).to.be.null;
+        if (feeBasisPoints > 0) {
+          // The fee withheld in the vault on the way in was harvested to the mint before closing.
+          const feeConfig = getTransferFeeConfig(await getMint(provider.connection, mint, undefined, tokenProgram));
+          expect(Number(feeConfig.withheldAmount)).to.equal(reward - escrowed);
+        }
+        expect(await provider.connection.getAccountInfo(findTokenBountyPda(task)[0])).to.be.null;
+      });
+
+      it("Refunds an expired bounty to the funder", async () => {
+        const task = await createTask(`${label} Refunded Task`);
+        await fund(task, Math.floor(Date.now() / 1000) + 2);
+        const refund = () =>
+          program.methods
+            .refundTokenBounty()
+            .accounts({ ...bountyAccounts(task), funderTokenAccount, funder: user.publicKey })
+            .signers([user.payer])
+            .rpc();
+        try {
+          await refund();
+          expect.fail("Should have failed because the bounty has not expired");
+        } catch (error) {
+          expect(error.toString()).to.include("BountyNotExpired");
+        }
+
+        const balanceBefore = await getAccount(provider.connection, funderTokenAccount, undefined, tokenProgram);
+        await new Promise((resolve) => setTimeout(resolve, 4000));
+        await refund();
+        const balanceAfter = await getAccount(provider.connection, funderTokenAccount, undefined, tokenProgram);
+        expect(Number(balanceAfter.amount - balanceBefore.amount)).to.equal(afterFee(afterFee(reward)));
+        expect(await provider.connection.getAccountInfo(bountyAccounts(task).vault)).to.be.null;
+      });
+    });
+  }
+
+  describe("commitment stakes", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const beneficiary = anchor.web3.Keypair.generate();
+    const slasher = anchor.web3.Keypair.generate();
+    const amount = anchor.web3.LAMPORTS_PER_SOL / 100;
+
+    // Deadlines follow the validator's clock, which can drift from the wall clock.
+    const now = async () => {
+      const clock = await provider.connection.getAccountInfo(anchor.web3.SYSVAR_CLOCK_PUBKEY);
+      return new BN(clock.data.subarray(32, 40), "le").toNumber();
+    };
+
+    const commitStake = (task: anchor.web3.PublicKey, stakeAmount: number) =>
+      program.methods
+        .commitStake(new BN(stakeAmount))
+        .accounts({
+          taskAccount: task,
+          stake: findStakePda(task)[0],
+          authority: user.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([user.payer])
+        .rpc();
+
+    const createStakedTask = async (name: string, secondsLeft: number, stakeAmount: number = amount) => {
+      const pda = await createTask(name, { dueAt: new BN((await now()) + secondsLeft) });
+      await commitStake(pda, stakeAmount);
+      return pda;
+    };
+
+    const slash = (task: anchor.web3.PublicKey) =>
+      program.methods
+        .slashStake()
+        .accounts({
+          taskAccount: task,
+          stake: findStakePda(task)[0],
+          staker: user.publicKey,
+          beneficiary: beneficiary.publicKey,
+          caller: slasher.publicKey,
+        })
+        .signers([slasher])
+        .rpc();
+
+    before(async () => {
+      await provider.connection.requestAirdrop(slasher.publicKey, anchor.web3.LAMPORTS_PER_SOL / 10);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.methods
+        .updateConfig({ paused: null, maxNameLength: null, taskFee: null, stakeBeneficiary: beneficiary.publicKey })
+        .accounts({ config: configPda, admin: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+    });
+
+    it("Refuses stakes too small to leave the beneficiary rent-exempt", async () => {
+      const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+      try {
+        await createStakedTask("Tiny Promise", 3600, rentExempt);
+        expect.fail("Should have failed because the stake is too small");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidStakeAmount");
+      }
+    });
+
+    it("Returns the stake when the task is done in time", async () => {
+      const task = await createStakedTask("Kept Promise", 3600);
+      const stake = await program.account.stake.fetch(findStakePda(task)[0]);
+      expect(stake.amount.toNumber()).to.equal(amount);
+      expect(stake.beneficiary.equals(beneficiary.publicKey)).to.be.true;
+
+      try {
+        await slash(task);
+        expect.fail("Should have failed before the deadline");
+      } catch (error) {
+        expect(error.toString()).to.include("StakeNotDue");
+      }
+
+      await program.methods
+        .updateTaskStatus(false, null)
+        .accounts({ taskAccount: task, userProfile: profilePda, authority: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      await program.methods
+        .claimStake()
+        .accounts({ taskAccount: task, stake: findStakePda(task)[0], staker: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(findStakePda(task)[0])).to.be.null;
+    });
+
+    it("Lets anyone slash a stake once the deadline is missed", async () => {
+      const task = await createStakedTask("Broken Promise", 2);
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      try {
+        await program.methods
+          .claimStake()
+          .accounts({ taskAccount: task, stake: findStakePda(task)[0], staker: user.publicKey })
+          .signers([user.payer])
+          .rpc();
+        expect.fail("Should have failed because the task is not done");
+      } catch (error) {
+        expect(error.toString()).to.include("StakeNotKept");
+      }
+
+      const slasherBefore = await provider.connection.getBalance(slasher.publicKey);
+      await slash(task);
+      const tip = amount / 100;
+      expect(await provider.connection.getBalance(beneficiary.publicKey)).to.equal(amount - tip);
+      expect(await provider.connection.getBalance(slasher.publicKey)).to.equal(slasherBefore + tip);
+      expect(await provider.connection.getAccountInfo(findStakePda(task)[0])).to.be.null;
+    });
+  });
+
+  describe("config", () => {
+    const [profilePda] = findProfilePda(user.publicKey);
+    const newAdmin = anchor.web3.Keypair.generate();
+
+    const updateConfig = (update: object, admin: anchor.web3.Keypair = user.payer) =>
+      program.methods
+        .updateConfig({ paused: null, maxNameLength: null, taskFee: null, stakeBeneficiary: null, ...update })
+        .accounts({ config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+    it("Rejects every instruction while paused", async () => {
+      await updateConfig({ paused: true });
+      try {
+        await createTask("Paused Task");
+        expect.fail("Should have failed because the program is paused");
+      } catch (error) {
+        expect(error.toString()).to.include("ProgramPaused");
+      }
+      await updateConfig({ paused: false });
+      await createTask("Unpaused Task");
+    });
+
+    it("Applies the configured max name length", async () => {
+      try {
+        await updateConfig({ maxNameLength: 129 });
+        expect.fail("Should have failed because the limit is exceeded");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidConfig");
+      }
+
+      await updateConfig({ maxNameLength: 80 });
+      const pda = await createTask("A".repeat(80));
+      expect((await program.account.taskAccount.fetch(pda)).name).to.have.lengthOf(80);
+      await updateConfig({ maxNameLength: 50 });
+    });
+
+    it("Charges the task fee and lets the admin withdraw it", async () => {
+      const fee = 5_000_000;
+      await updateConfig({ taskFee: new BN(fee) });
+      const configBefore = await provider.connection.getBalance(configPda);
+      await createTask("Paid Task");
+      expect(await provider.connection.getBalance(configPda)).to.equal(configBefore + fee);
+      await updateConfig({ taskFee: new BN(0) });
+
+      await program.methods
+        .withdrawFees()
+        .accounts({ config: configPda, admin: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      const info = await provider.connection.getAccountInfo(configPda);
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(info.data.length);
+      expect(info.lamports).to.equal(rent);
+    });
+
+    it("Rotates the admin in two steps", async () => {
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({ config: configPda, admin: user.publicKey })
+        .signers([user.payer])
+        .rpc();
+      try {
+        await updateConfig({ paused: false }, newAdmin);
+        expect.fail("Should have failed before the rotation is accepted");
+      } catch (error) {
+        expect(error.toString()).to.include("UnauthorizedAction");
+      }
+
+      await program.methods
+        .acceptAdmin()
+        .accounts({ config: configPda, newAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+      const config = await program.account.config.fetch(configPda);
+      expect(config.admin.equals(newAdmin.publicKey)).to.be.true;
+      expect(config.pendingAdmin).to.be.null;
+    });
+  });
 });